@@ -0,0 +1,444 @@
+#![allow(dead_code)]
+
+mod tests;
+
+use std::collections::HashMap;
+
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::utils::{
+    get_case_insensitive_corpus, get_english_byte_bigrams, get_english_byte_frequencies,
+    get_english_corpus, get_log_likelihood_of_english, index_of_coincidence, quadgram_index,
+    transpose,
+};
+
+/// Runs every letter of `text` through `f` as 0..26 and keeps its case. Anything else is left alone.
+fn map_letters<F: FnMut(u8) -> u8>(text: &str, mut f: F) -> String {
+    let bytes = text
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_uppercase() {
+                b'A' + f(b - b'A') % 26
+            } else if b.is_ascii_lowercase() {
+                b'a' + f(b - b'a') % 26
+            } else {
+                b
+            }
+        })
+        .collect();
+
+    // only ascii letters change, so whatever else was there is still valid utf-8
+    String::from_utf8(bytes).unwrap()
+}
+
+/// The letters of `text`, uppercased.
+fn letters_of(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(|b| b.to_ascii_uppercase())
+        .collect()
+}
+
+pub fn caesar_encrypt(text: &str, shift: u8) -> String {
+    map_letters(text, |l| l + shift % 26)
+}
+
+pub fn caesar_decrypt(text: &str, shift: u8) -> String {
+    caesar_encrypt(text, 26 - shift % 26)
+}
+
+/// Tries all 26 shifts and keeps the one that reads most like English.
+pub fn break_caesar(text: &str) -> (u8, String) {
+    let corpus = get_case_insensitive_corpus(&get_english_corpus());
+    break_caesar_with(text, &corpus)
+}
+
+fn break_caesar_with(text: &str, corpus: &HashMap<char, f64>) -> (u8, String) {
+    (0..26u8)
+        .map(|shift| {
+            let plain_text = caesar_decrypt(text, shift);
+            let score = get_log_likelihood_of_english(plain_text.as_bytes(), corpus);
+            (shift, plain_text, score)
+        })
+        .max_by(|(_, _, s1), (_, _, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(shift, plain_text, _)| (shift, plain_text))
+        .unwrap()
+}
+
+/// Letters that aren't part of `key` don't use up a key letter. `key` needs at least one letter.
+pub fn vigenere_encrypt(text: &str, key: &str) -> String {
+    let shifts: Vec<u8> = letters_of(key).iter().map(|k| k - b'A').collect();
+    if shifts.is_empty() {
+        panic!("{:?} has no letters to use as a key", key)
+    }
+    let mut i = 0;
+
+    map_letters(text, |l| {
+        let shift = shifts[i % shifts.len()];
+        i += 1;
+        l + shift
+    })
+}
+
+pub fn vigenere_decrypt(text: &str, key: &str) -> String {
+    let inverse: String = letters_of(key)
+        .iter()
+        .map(|k| (b'A' + (26 - (k - b'A')) % 26) as char)
+        .collect();
+    vigenere_encrypt(text, &inverse)
+}
+
+/// Finds the key length from the index of coincidence of the transposed letters, then breaks
+/// every column as a Caesar shift. Multiples of the key length score just as well, so the
+/// shortest length that gets close to the best is taken. `None` if `text` has no letters or
+/// `max_key_len` is 0.
+pub fn break_vigenere(text: &str, max_key_len: usize) -> Option<(String, String)> {
+    let letters = letters_of(text);
+    let period_ioc: Vec<(usize, f64)> = (1..=max_key_len.min(letters.len()))
+        .map(|p| {
+            let columns = transpose(&letters[..letters.len() - letters.len() % p], p);
+            let ioc = columns.iter().map(|c| index_of_coincidence(c)).sum::<f64>() / p as f64;
+            (p, ioc)
+        })
+        .collect();
+
+    let best = period_ioc.iter().map(|(_, ioc)| *ioc).fold(0.0, f64::max);
+    let (key_len, _) = *period_ioc.iter().find(|(_, ioc)| *ioc >= best * 0.9)?;
+
+    let corpus = get_case_insensitive_corpus(&get_english_corpus());
+    let key: String = transpose(&letters[..letters.len() - letters.len() % key_len], key_len)
+        .iter()
+        .map(|column| {
+            let (shift, _) = break_caesar_with(&String::from_utf8_lossy(column), &corpus);
+            (b'A' + shift) as char
+        })
+        .collect();
+
+    let plain_text = vigenere_decrypt(text, &key);
+    Some((key, plain_text))
+}
+
+/// Multiplicative inverse of `a` mod 26, if there is one.
+fn inverse_mod_26(a: u8) -> Option<u8> {
+    (1..26u8).find(|x| (a as usize * *x as usize) % 26 == 1)
+}
+
+/// `a * x + b`. `a` has to be coprime with 26 or there's no way back.
+pub fn affine_encrypt(text: &str, a: u8, b: u8) -> String {
+    if inverse_mod_26(a).is_none() {
+        panic!("{} has no inverse mod 26", a)
+    }
+
+    map_letters(text, |l| {
+        ((a as usize * l as usize + b as usize) % 26) as u8
+    })
+}
+
+pub fn affine_decrypt(text: &str, a: u8, b: u8) -> String {
+    let inverse = inverse_mod_26(a).expect("a has no inverse mod 26");
+    map_letters(text, |l| {
+        ((inverse as usize * (l as usize + 26 - b as usize % 26)) % 26) as u8
+    })
+}
+
+/// Only 12 * 26 keys, so tries them all.
+pub fn break_affine(text: &str) -> ((u8, u8), String) {
+    let corpus = get_case_insensitive_corpus(&get_english_corpus());
+
+    (1..26u8)
+        .filter(|a| inverse_mod_26(*a).is_some())
+        .flat_map(|a| (0..26u8).map(move |b| (a, b)))
+        .map(|(a, b)| {
+            let plain_text = affine_decrypt(text, a, b);
+            let score = get_log_likelihood_of_english(plain_text.as_bytes(), &corpus);
+            ((a, b), plain_text, score)
+        })
+        .max_by(|(_, _, s1), (_, _, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(key, plain_text, _)| (key, plain_text))
+        .unwrap()
+}
+
+/// Panics unless `key` has every uppercase letter once.
+fn check_substitution_key(key: &[u8; 26]) {
+    let mut sorted = *key;
+    sorted.sort();
+    if sorted != *b"ABCDEFGHIJKLMNOPQRSTUVWXYZ" {
+        panic!(
+            "{:?} isn't the uppercase alphabet in some order",
+            String::from_utf8_lossy(key)
+        )
+    }
+}
+
+/// `key[i]` is the uppercase letter that the i-th letter of the alphabet turns into.
+pub fn substitution_encrypt(text: &str, key: &[u8; 26]) -> String {
+    check_substitution_key(key);
+    map_letters(text, |l| key[l as usize] - b'A')
+}
+
+pub fn substitution_decrypt(text: &str, key: &[u8; 26]) -> String {
+    check_substitution_key(key);
+    let mut inverse = [0u8; 26];
+    for (plain, cipher) in key.iter().enumerate() {
+        inverse[(cipher - b'A') as usize] = plain as u8;
+    }
+    map_letters(text, |l| inverse[l as usize])
+}
+
+/// Hill-climbs on quadgram scores: swap two letters of the key, keep the swap if the text got
+/// more English. Each of the `restarts` climbs from a fresh random key and gives up after
+/// `patience` swaps in a row that didn't help. Deterministic for a given `seed`. `quadgrams`
+/// comes from `get_english_quadgrams`.
+pub fn break_substitution(
+    text: &str,
+    quadgrams: &[f64],
+    restarts: usize,
+    patience: usize,
+    seed: u64,
+) -> ([u8; 26], String) {
+    let cipher: Vec<u8> = letters_of(text).iter().map(|l| l - b'A').collect();
+    let score = |decryption: &[u8; 26]| -> f64 {
+        let plain: Vec<u8> = cipher.iter().map(|&c| decryption[c as usize]).collect();
+        plain.windows(4).map(|q| quadgrams[quadgram_index(q)]).sum()
+    };
+
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut best = [0u8; 26];
+    let mut best_score = f64::NEG_INFINITY;
+
+    for _ in 0..restarts.max(1) {
+        let mut decryption: [u8; 26] = std::array::from_fn(|i| i as u8);
+        decryption.shuffle(&mut rng);
+        let mut current = score(&decryption);
+
+        let mut stale = 0;
+        while stale < patience {
+            let (i, j) = (rng.gen_range(0..26), rng.gen_range(0..26));
+            decryption.swap(i, j);
+
+            let candidate = score(&decryption);
+            if candidate > current {
+                current = candidate;
+                stale = 0;
+            } else {
+                decryption.swap(i, j);
+                stale += 1;
+            }
+        }
+
+        if current > best_score {
+            best_score = current;
+            best = decryption;
+        }
+    }
+
+    // turn the cipher -> plain mapping around into an encryption key
+    let mut key = [0u8; 26];
+    for (cipher, plain) in best.iter().enumerate() {
+        key[*plain as usize] = b'A' + cipher as u8;
+    }
+
+    let plain_text = substitution_decrypt(text, &key);
+    (key, plain_text)
+}
+
+/// How many of the most common cipher byte pairs get tried against four times as many of the
+/// most common English pairs once annealing is done.
+const PAIR_SEARCH_WIDTH: usize = 25;
+
+/// How long `break_byte_substitution` searches for. The temperature is measured against the
+/// score per byte, so the same schedule works for short and long cipher texts.
+/// `polish_rounds` caps the greedy passes after annealing, each of which tries every swap.
+pub struct AnnealingSchedule {
+    pub restarts: usize,
+    pub iterations: usize,
+    pub start_temperature: f64,
+    pub polish_rounds: usize,
+    pub seed: u64,
+}
+
+impl Default for AnnealingSchedule {
+    fn default() -> Self {
+        AnnealingSchedule {
+            restarts: 2,
+            iterations: 5_000,
+            start_temperature: 0.05,
+            polish_rounds: 4,
+            seed: 0,
+        }
+    }
+}
+
+/// Sends every byte through `table`.
+pub fn byte_substitution(bytes: &[u8], table: &[u8; 256]) -> Vec<u8> {
+    bytes.iter().map(|&b| table[b as usize]).collect()
+}
+
+/// The table that undoes `table`. Panics if `table` isn't a permutation.
+pub fn invert_byte_table(table: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0u8; 256];
+    let mut seen = [false; 256];
+    for (from, &to) in table.iter().enumerate() {
+        if seen[to as usize] {
+            panic!("{} shows up twice, not a permutation", to)
+        }
+        seen[to as usize] = true;
+        inverse[to as usize] = from as u8;
+    }
+    inverse
+}
+
+/// Scores bytes by how likely each consecutive pair is in the English corpus.
+pub fn english_byte_scorer() -> impl Fn(&[u8]) -> f64 {
+    let bigrams = get_english_byte_bigrams();
+    move |bytes: &[u8]| {
+        bytes
+            .windows(2)
+            .map(|pair| bigrams[pair[0] as usize * 256 + pair[1] as usize])
+            .sum()
+    }
+}
+
+/// Anneals towards the permutation of all 256 byte values that undoes `cipher`, starting each
+/// restart from the byte frequencies. The best table seen is finished off greedily and
+/// returned with the plain text it gives.
+pub fn break_byte_substitution<F: Fn(&[u8]) -> f64>(
+    cipher: &[u8],
+    scorer: &F,
+    schedule: &AnnealingSchedule,
+) -> ([u8; 256], Vec<u8>) {
+    let mut counts = [0usize; 256];
+    for &b in cipher {
+        counts[b as usize] += 1;
+    }
+    let mut cipher_bytes: Vec<u8> = (0..=255u8).collect();
+    cipher_bytes.sort_by_key(|&b| std::cmp::Reverse(counts[b as usize]));
+    let present: Vec<u8> = cipher_bytes
+        .iter()
+        .copied()
+        .filter(|&b| counts[b as usize] > 0)
+        .collect();
+
+    let frequencies = get_english_byte_frequencies();
+    let freq = |b: &u8| frequencies[*b as usize];
+    let mut english_bytes: Vec<u8> = (0..=255u8).collect();
+    english_bytes.sort_by(|a, b| freq(b).partial_cmp(&freq(a)).unwrap());
+    // bytes English never uses aren't worth decrypting to
+    let candidates: Vec<u8> = english_bytes
+        .iter()
+        .copied()
+        .filter(|b| freq(b) > 0.0)
+        .collect();
+
+    let mut start = [0u8; 256];
+    for (c, p) in cipher_bytes.iter().zip(english_bytes.iter()) {
+        start[*c as usize] = *p;
+    }
+
+    if present.is_empty() || candidates.is_empty() {
+        return (start, vec![]);
+    }
+
+    let per_byte =
+        |table: &[u8; 256]| scorer(&byte_substitution(cipher, table)) / cipher.len() as f64;
+    // the byte that currently decrypts to `plain`, so swapping with it hands `plain` over
+    let partner = |table: &[u8; 256], plain: u8| table.iter().position(|&p| p == plain).unwrap();
+
+    let mut rng = SmallRng::seed_from_u64(schedule.seed);
+    let mut best = (start, per_byte(&start));
+
+    for _ in 0..schedule.restarts.max(1) {
+        let mut table = start;
+        let mut current = per_byte(&table);
+
+        for i in 0..schedule.iterations {
+            let temperature =
+                schedule.start_temperature * (1.0 - i as f64 / schedule.iterations as f64);
+
+            let a = present[rng.gen_range(0..present.len())] as usize;
+            let b = partner(&table, candidates[rng.gen_range(0..candidates.len())]);
+            if a == b {
+                continue;
+            }
+
+            table.swap(a, b);
+            let candidate = per_byte(&table);
+            let delta = candidate - current;
+
+            if delta >= 0.0 || (temperature > 0.0 && rng.gen::<f64>() < (delta / temperature).exp())
+            {
+                current = candidate;
+                if current > best.1 {
+                    best = (table, current);
+                }
+            } else {
+                table.swap(a, b);
+            }
+        }
+    }
+
+    // the walk can end just short of a peak. common cipher pairs sent to common English pairs
+    // unstick bytes that only make sense together, like `\r\n`
+    let mut cipher_pairs: Vec<(u8, u8)> = cipher.windows(2).map(|w| (w[0], w[1])).collect();
+    cipher_pairs.sort();
+    let mut pair_counts: Vec<(usize, (u8, u8))> = cipher_pairs
+        .chunk_by(|a, b| a == b)
+        .map(|run| (run.len(), run[0]))
+        .filter(|(_, (x, y))| x != y)
+        .collect();
+    pair_counts.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+
+    let bigrams = get_english_byte_bigrams();
+    let mut english_pairs: Vec<usize> = (0..bigrams.len()).collect();
+    english_pairs.sort_by(|a, b| bigrams[*b].partial_cmp(&bigrams[*a]).unwrap());
+
+    let (mut table, mut current) = best;
+    for _ in 0..schedule.polish_rounds {
+        let mut improved = false;
+
+        for &a in &present {
+            for &plain in &candidates {
+                let (a, b) = (a as usize, partner(&table, plain));
+                if a == b {
+                    continue;
+                }
+                table.swap(a, b);
+                let candidate = per_byte(&table);
+                if candidate > current {
+                    current = candidate;
+                    improved = true;
+                } else {
+                    table.swap(a, b);
+                }
+            }
+        }
+
+        for (_, (x, y)) in pair_counts.iter().take(PAIR_SEARCH_WIDTH) {
+            for &pair in english_pairs.iter().take(PAIR_SEARCH_WIDTH * 4) {
+                let (p, q) = ((pair / 256) as u8, (pair % 256) as u8);
+                if p == q || (table[*x as usize], table[*y as usize]) == (p, q) {
+                    continue;
+                }
+
+                let saved = table;
+                let holder = partner(&table, p);
+                table.swap(*x as usize, holder);
+                let holder = partner(&table, q);
+                table.swap(*y as usize, holder);
+                let candidate = per_byte(&table);
+                if candidate > current {
+                    current = candidate;
+                    improved = true;
+                } else {
+                    table = saved;
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    (table, byte_substitution(cipher, &table))
+}
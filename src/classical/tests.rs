@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod classical_tests {
+    use std::fs;
+
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+    use crate::classical::*;
+    use crate::utils::get_english_quadgrams;
+
+    fn excerpt(start: usize, len: usize) -> String {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        String::from_utf8_lossy(&text.as_bytes()[start..start + len]).to_string()
+    }
+
+    #[test]
+    fn test_caesar() {
+        let encrypted = caesar_encrypt("Veni, vidi, vici", 3);
+        assert_eq!("Yhql, ylgl, ylfl", encrypted);
+        assert_eq!("Veni, vidi, vici", caesar_decrypt(&encrypted, 3));
+
+        let plain_text = excerpt(10000, 300);
+        let (shift, res) = break_caesar(&caesar_encrypt(&plain_text, 17));
+        assert_eq!(17, shift);
+        assert_eq!(plain_text, res);
+    }
+
+    #[test]
+    fn test_vigenere() {
+        let encrypted = vigenere_encrypt("ATTACK AT DAWN", "LEMON");
+        assert_eq!("LXFOPV EF RNHR", encrypted);
+        assert_eq!("ATTACK AT DAWN", vigenere_decrypt(&encrypted, "LEMON"));
+
+        let plain_text = excerpt(20000, 2000);
+        let (key, res) = break_vigenere(&vigenere_encrypt(&plain_text, "MRDARCY"), 20).unwrap();
+        assert_eq!("MRDARCY", key);
+        assert_eq!(plain_text, res);
+
+        assert_eq!(None, break_vigenere("1234 !?", 20));
+        assert_eq!(None, break_vigenere("LXFOPV EF RNHR", 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_vigenere_needs_a_letter_in_the_key() {
+        vigenere_encrypt("ATTACK AT DAWN", "42");
+    }
+
+    #[test]
+    fn test_affine() {
+        let encrypted = affine_encrypt("AFFINE cipher", 5, 8);
+        assert_eq!("IHHWVC swfrcp", encrypted);
+        assert_eq!("AFFINE cipher", affine_decrypt(&encrypted, 5, 8));
+
+        let plain_text = excerpt(30000, 300);
+        let (key, res) = break_affine(&affine_encrypt(&plain_text, 11, 20));
+        assert_eq!((11, 20), key);
+        assert_eq!(plain_text, res);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_affine_needs_invertible_multiplier() {
+        affine_encrypt("nope", 13, 1);
+    }
+
+    #[test]
+    fn test_substitution() {
+        let key = *b"QWERTYUIOPASDFGHJKLZXCVBNM";
+        let encrypted = substitution_encrypt("Hello, World", &key);
+        assert_eq!("Itssg, Vgksr", encrypted);
+        assert_eq!("Hello, World", substitution_decrypt(&encrypted, &key));
+
+        let plain_text = excerpt(40000, 2000);
+        let quadgrams = get_english_quadgrams();
+        let cipher = substitution_encrypt(&plain_text, &key);
+        let (_, res) = break_substitution(&cipher, &quadgrams, 5, 2000, 7);
+        assert_eq!(plain_text, res);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_substitution_key_must_be_the_alphabet() {
+        substitution_encrypt("Hello", b"qwertyuiopasdfghjklzxcvbnm");
+    }
+
+    #[test]
+    fn test_byte_substitution() {
+        let mut rng = SmallRng::seed_from_u64(38);
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        table.shuffle(&mut rng);
+
+        let plain_text = excerpt(50000, 3000).into_bytes();
+        let cipher = byte_substitution(&plain_text, &table);
+        assert_eq!(
+            plain_text,
+            byte_substitution(&cipher, &invert_byte_table(&table))
+        );
+
+        let schedule = AnnealingSchedule {
+            seed: 1,
+            ..AnnealingSchedule::default()
+        };
+        let scorer = english_byte_scorer();
+        let (_, res) = break_byte_substitution(&cipher, &scorer, &schedule);
+
+        // rare capitals and digits can't always be told apart, but the bulk of the text can
+        for (a, b) in res.iter().zip(plain_text.iter()) {
+            if b.is_ascii_lowercase() || *b == b' ' {
+                assert_eq!(b, a);
+            }
+        }
+        let correct = res
+            .iter()
+            .zip(plain_text.iter())
+            .filter(|(a, b)| a == b)
+            .count();
+        assert!(correct as f64 / plain_text.len() as f64 > 0.95);
+
+        // same seed, same answer
+        let quick = AnnealingSchedule {
+            restarts: 1,
+            iterations: 1_000,
+            polish_rounds: 1,
+            seed: 1,
+            ..AnnealingSchedule::default()
+        };
+        assert_eq!(
+            break_byte_substitution(&cipher[..500], &scorer, &quick),
+            break_byte_substitution(&cipher[..500], &scorer, &quick)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use base64::{engine::general_purpose, Engine};
+use rand::{thread_rng, Rng};
+
+use crate::set_3::{break_fixed_nonce_ctr, use_ctr_mode, CribDragger};
+
+const CRIB_HELP: &str = "commands:
+  show                          print every line under the current keystream
+  auto <min samples> [min conf] fill the keystream from column statistics
+  drag <crib>                   rank where <crib> fits
+  pin <line> <offset> <text>    make <line> read <text> from <offset>
+  unpin <offset> <len>          forget keystream bytes
+  help
+  quit";
+
+/// `cryptopals crib <file> [--encrypt]`
+///
+/// Reads one base64 cipher text per line. With `--encrypt` the lines are plain texts instead and
+/// get encrypted under fixed-nonce CTR with a random key first, which is handy for practice on
+/// `texts/20.txt`.
+pub fn run_crib_drag(args: &[String]) {
+    let Some(path) = args.first() else {
+        eprintln!("usage: cryptopals crib <file of base64 lines> [--encrypt]");
+        return;
+    };
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("can't read {}: {}", path, e);
+            return;
+        }
+    };
+    let decoded: Result<Vec<Vec<u8>>, _> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| general_purpose::STANDARD.decode(line.trim()))
+        .collect();
+    let mut lines = match decoded {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("{} isn't all base64 lines: {}", path, e);
+            return;
+        }
+    };
+
+    if args.iter().any(|a| a == "--encrypt") {
+        let key: [u8; 16] = thread_rng().gen();
+        lines = lines
+            .iter()
+            .map(|line| use_ctr_mode(&key, &[0; 8], line))
+            .collect();
+    }
+
+    let mut dragger = CribDragger::new(lines);
+    let stdin = io::stdin();
+    crib_drag_session(&mut dragger, stdin.lock(), &mut io::stdout()).unwrap();
+}
+
+/// Reads commands from `input` until `quit` or the end of it, see `CRIB_HELP`.
+pub fn crib_drag_session<R: BufRead, W: Write>(
+    dragger: &mut CribDragger,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    writeln!(
+        output,
+        "{} lines loaded, `help` for commands",
+        dragger.ciphers().len()
+    )?;
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut parts = line.splitn(2, ' ');
+        let command = parts.next().unwrap_or("");
+        let rest = parts.next().unwrap_or("");
+
+        match command {
+            "show" => show(dragger, output)?,
+            "auto" => {
+                let mut numbers = rest.split_whitespace();
+                let min_samples = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(5);
+                let min_confidence = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(0.9);
+
+                let guesses = break_fixed_nonce_ctr(dragger.ciphers(), min_samples);
+                dragger.seed(&guesses, min_confidence);
+                show(dragger, output)?;
+            }
+            "drag" if !rest.is_empty() => {
+                for (rank, m) in dragger.drag(rest.as_bytes()).iter().take(10).enumerate() {
+                    writeln!(
+                        output,
+                        "#{} line {} @ {} ({:.2})",
+                        rank, m.crib_in, m.offset, m.score
+                    )?;
+                    for (other, revealed) in m.revealed.iter().take(3) {
+                        writeln!(
+                            output,
+                            "    {:>3}: {:?}",
+                            other,
+                            String::from_utf8_lossy(revealed)
+                        )?;
+                    }
+                }
+            }
+            "pin" => {
+                let mut args = rest.splitn(3, ' ');
+                let index = args.next().and_then(|n| n.parse::<usize>().ok());
+                let offset = args.next().and_then(|n| n.parse().ok());
+                match (index, offset, args.next()) {
+                    (Some(index), Some(offset), Some(text)) if index < dragger.ciphers().len() => {
+                        dragger.pin(index, offset, text.as_bytes());
+                        show(dragger, output)?;
+                    }
+                    _ => writeln!(output, "usage: pin <line> <offset> <text>")?,
+                }
+            }
+            "unpin" => {
+                let mut numbers = rest.split_whitespace().map(|n| n.parse::<usize>().ok());
+                match (numbers.next().flatten(), numbers.next().flatten()) {
+                    (Some(offset), Some(len)) => {
+                        dragger.unpin(offset, len);
+                        show(dragger, output)?;
+                    }
+                    _ => writeln!(output, "usage: unpin <offset> <len>")?,
+                }
+            }
+            "quit" | "exit" => break,
+            "" => {}
+            _ => writeln!(output, "{}", CRIB_HELP)?,
+        }
+
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+fn show<W: Write>(dragger: &CribDragger, output: &mut W) -> io::Result<()> {
+    for (i, line) in dragger.render().iter().enumerate() {
+        writeln!(output, "{:>3} {}", i, line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use crate::cli::crib_drag_session;
+    use crate::set_1::fixed_xor;
+    use crate::set_3::CribDragger;
+
+    #[test]
+    fn test_crib_drag_session() {
+        let keystream = b"not a random keystream at all!!";
+        let ciphers = vec![
+            fixed_xor(b"attack at dawn, bring the boats", keystream),
+            fixed_xor(b"retreat at noon and hide", keystream),
+        ];
+        let mut dragger = CribDragger::new(ciphers);
+
+        let input = "drag at dawn\npin 0 0 attack at dawn\nquit\nshow\n".as_bytes();
+        let mut output = vec![];
+        crib_drag_session(&mut dragger, input, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  0 attack at dawn_________________"));
+        assert!(output.contains("  1 retreat at noo_________"));
+        // nothing after quit runs
+        assert_eq!(1, output.matches("  0 attack").count());
+    }
+}
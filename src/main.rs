@@ -1,8 +1,19 @@
+use std::env;
+
+mod classical;
+mod cli;
+mod prng;
 mod set_1;
 mod set_2;
 mod set_3;
+mod set_4;
 mod utils;
 
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(|a| a.as_str()) {
+        Some("crib") => cli::run_crib_drag(&args[2..]),
+        _ => eprintln!("usage: cryptopals crib <file of base64 lines> [--encrypt]"),
+    }
 }
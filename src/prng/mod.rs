@@ -0,0 +1,383 @@
+#![allow(dead_code)]
+
+mod tests;
+
+use crate::set_3::{offsets_around, seeds_around, Clock, SeedResolution};
+use crate::utils::{back_substitute, eliminate, row_bit, Gf2Equation, Reduction};
+
+/// glibc's `rand()` with its default amount of state: an additive feedback generator where
+/// every number is the sum of the ones 3 and 31 before it, handed out without its lowest bit.
+pub struct GlibcRandom {
+    /// The last 31 sums, oldest at `index`.
+    table: [u32; 31],
+    index: usize,
+}
+
+impl GlibcRandom {
+    /// `srand(seed)`: fills the table from a Lehmer generator, then throws away 310 numbers.
+    pub fn new(seed: u32) -> Self {
+        let mut r = [0u32; 34];
+        r[0] = seed.max(1);
+        for i in 1..31 {
+            // 16807 * r mod 2^31 - 1 without overflowing, on signed words like glibc
+            let previous = r[i - 1] as i32;
+            let (hi, lo) = (previous / 127773, previous % 127773);
+            let word = 16807i32.wrapping_mul(lo).wrapping_sub(2836 * hi);
+            r[i] = if word < 0 { word + 2147483647 } else { word } as u32;
+        }
+        r.copy_within(0..3, 31);
+
+        let mut table = [0; 31];
+        table.copy_from_slice(&r[3..]);
+        let mut rng = Self { table, index: 0 };
+        for _ in 0..310 {
+            rng.step();
+        }
+        rng
+    }
+
+    fn step(&mut self) -> u32 {
+        let sum = self.table[self.index].wrapping_add(self.table[(self.index + 28) % 31]);
+        self.table[self.index] = sum;
+        self.index = (self.index + 1) % 31;
+        sum
+    }
+
+    /// `rand()`.
+    pub fn next(&mut self) -> u32 {
+        self.step() >> 1
+    }
+
+    /// Rebuilds the table from consecutive outputs of `rand()` and picks up after them.
+    ///
+    /// With the lowest bits gone, `o[k] = o[k - 3] + o[k - 31]`, plus one when both of the
+    /// dropped bits were set. Every time that happens gives away two of them, and the dropped
+    /// bits follow `b[k] = b[k - 3] ^ b[k - 31]` on their own, so each is the XOR of some of the
+    /// first 31. A few hundred outputs are usually enough to pin those down. `None` if they
+    /// weren't, or the outputs didn't come one after another from one generator.
+    pub fn clone_from_outputs(outputs: &[u32]) -> Option<Self> {
+        // which of the first 31 dropped bits each dropped bit is the XOR of
+        let mut dropped: Vec<u64> = (0..31.min(outputs.len())).map(|k| 1 << k).collect();
+        let mut pivots: Vec<Option<Gf2Equation>> = vec![None; 31];
+        let mut unpinned = 31;
+
+        for k in 31..outputs.len() {
+            dropped.push(dropped[k - 3] ^ dropped[k - 31]);
+            let sum = outputs[k - 3].wrapping_add(outputs[k - 31]) & 0x7FFFFFFF;
+            if outputs[k] == sum {
+                continue;
+            }
+            if outputs[k] != (sum + 1) & 0x7FFFFFFF {
+                return None;
+            }
+
+            for set in [k - 3, k - 31] {
+                match eliminate(&mut pivots, vec![dropped[set]], true) {
+                    Reduction::Pivot(_) => unpinned -= 1,
+                    Reduction::Contradiction => return None,
+                    Reduction::Redundant => {}
+                }
+            }
+        }
+        if unpinned > 0 {
+            return None;
+        }
+
+        let first_bits = back_substitute(&pivots);
+        let mut table = [0; 31];
+        for (k, word) in table.iter_mut().enumerate() {
+            *word = outputs[k] << 1 | row_bit(&first_bits, k) as u32;
+        }
+
+        let mut clone = Self { table, index: 0 };
+        for &output in &outputs[31..] {
+            if clone.next() != output {
+                return None;
+            }
+        }
+        Some(clone)
+    }
+}
+
+/// Goes through the times around now to find the `srand` seed of a glibc `rand()` whose first
+/// number was `generated_number`.
+pub fn crack_glibc_seed<C: Clock>(
+    clock: &C,
+    resolution: SeedResolution,
+    generated_number: u32,
+    behind: u32,
+    ahead: u32,
+) -> Option<u32> {
+    let now = resolution.seed_at(clock.now());
+    seeds_around(now, behind, ahead).find(|&seed| GlibcRandom::new(seed).next() == generated_number)
+}
+
+const JAVA_MULTIPLIER: u64 = 0x5DEECE66D;
+const JAVA_ADDEND: u64 = 0xB;
+const JAVA_MASK: u64 = (1 << 48) - 1;
+
+/// `java.util.Random`: a 48-bit LCG that hands out the top bits of its state.
+pub struct JavaRandom {
+    state: u64,
+}
+
+impl JavaRandom {
+    /// `new Random(seed)`, which scrambles the seed before using it.
+    pub fn new(seed: i64) -> Self {
+        Self::from_state(seed as u64 ^ JAVA_MULTIPLIER)
+    }
+
+    /// A generator whose state between calls is `state`.
+    pub fn from_state(state: u64) -> Self {
+        Self {
+            state: state & JAVA_MASK,
+        }
+    }
+
+    /// `next(bits)`: steps the LCG and gives back the top `bits` of the new state.
+    fn next(&mut self, bits: u8) -> i32 {
+        self.state = self
+            .state
+            .wrapping_mul(JAVA_MULTIPLIER)
+            .wrapping_add(JAVA_ADDEND)
+            & JAVA_MASK;
+        (self.state >> (48 - bits)) as i32
+    }
+
+    /// `nextInt()`.
+    pub fn next_int(&mut self) -> i32 {
+        self.next(32)
+    }
+
+    /// `nextInt(bound)`. Powers of two take the top bits, anything else takes the remainder
+    /// of 31 bits and rerolls the few at the very top that would make it uneven. Panics if
+    /// `bound` isn't positive.
+    pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+        assert!(bound > 0, "bound must be positive");
+        if bound & (bound - 1) == 0 {
+            return ((bound as i64 * self.next(31) as i64) >> 31) as i32;
+        }
+
+        loop {
+            let bits = self.next(31);
+            let val = bits % bound;
+            if (bits - val).checked_add(bound - 1).is_some() {
+                return val;
+            }
+        }
+    }
+
+    /// `nextLong()`: two numbers, the first on top.
+    pub fn next_long(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+
+    /// `nextDouble()`: 53 bits out of two numbers.
+    pub fn next_double(&mut self) -> f64 {
+        let bits = ((self.next(26) as i64) << 27) + self.next(27) as i64;
+        bits as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Clones a generator from consecutive `nextInt()` outputs, each the top 32 bits of the
+    /// state, which leaves 2^16 states to try for the first one.
+    pub fn clone_from_ints(outputs: &[i32]) -> Option<Self> {
+        let tops: Vec<u64> = outputs.iter().map(|&output| output as u32 as u64).collect();
+        Self::clone_from_high_bits(&tops, 32)
+    }
+
+    /// Clones a generator from the top `bits` of its state after each of a run of calls, which
+    /// is what `next(bits)` gives and what `nextInt` of a power of two comes down to. Tries
+    /// everything the rest of the first state could be and keeps what the other outputs agree
+    /// with, so it takes 2^(48 - bits) steps: fine from 32 bits down to the low 20s. Picks up
+    /// after the last output, and `None` means there were fewer than two or nothing fit.
+    pub fn clone_from_high_bits(outputs: &[u64], bits: u8) -> Option<Self> {
+        let hidden = 48 - bits;
+        let (&first, rest) = outputs.split_first()?;
+        if rest.is_empty() {
+            return None;
+        }
+
+        (0..1u64 << hidden).find_map(|low| {
+            let mut rng = Self::from_state(first << hidden | low);
+            let fits = rest.iter().all(|&top| rng.next(bits) as u32 as u64 == top);
+            fits.then_some(rng)
+        })
+    }
+}
+
+/// Goes through the times around now to find the seed of a `new Random(time)` whose first
+/// `nextInt()` was `generated_number`. Java seeds take the whole time, not just 32 bits of it.
+pub fn crack_java_seed<C: Clock>(
+    clock: &C,
+    resolution: SeedResolution,
+    generated_number: i32,
+    behind: u32,
+    ahead: u32,
+) -> Option<i64> {
+    let now = resolution.count_at(clock.now()) as i64;
+    offsets_around(behind, ahead)
+        .map(|offset| now + offset)
+        .find(|&seed| JavaRandom::new(seed).next_int() == generated_number)
+}
+
+/// Xorshift128+, which V8 uses for `Math.random()`.
+pub struct Xorshift128Plus {
+    state0: u64,
+    state1: u64,
+}
+
+/// How many doubles V8 makes at a time for `Math.random()`.
+const V8_CACHE_SIZE: usize = 64;
+
+/// MurmurHash3's 64-bit finalizer, which V8 seeds with.
+fn murmur_finalize(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51AFD7ED558CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CEB9FE1A85EC53);
+    h ^ h >> 33
+}
+
+/// A word of the state as the bits of the starting state each of its bits is the XOR of,
+/// with `state0` in the low 64 and `state1` in the high 64.
+type SymbolicWord = [u128; 64];
+
+fn symbolic_shl(x: &SymbolicWord, n: usize) -> SymbolicWord {
+    let mut shifted = [0; 64];
+    shifted[n..].copy_from_slice(&x[..64 - n]);
+    shifted
+}
+
+fn symbolic_shr(x: &SymbolicWord, n: usize) -> SymbolicWord {
+    let mut shifted = [0; 64];
+    shifted[..64 - n].copy_from_slice(&x[n..]);
+    shifted
+}
+
+fn symbolic_xor(x: &SymbolicWord, y: &SymbolicWord) -> SymbolicWord {
+    std::array::from_fn(|bit| x[bit] ^ y[bit])
+}
+
+impl Xorshift128Plus {
+    /// Seeded the way V8 seeds `Math.random()` from `--random-seed`.
+    pub fn new(seed: u64) -> Self {
+        Self::from_state(murmur_finalize(seed), murmur_finalize(!seed))
+    }
+
+    pub fn from_state(state0: u64, state1: u64) -> Self {
+        Self { state0, state1 }
+    }
+
+    fn step(&mut self) {
+        let mut s1 = self.state0;
+        let s0 = self.state1;
+        self.state0 = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state1 = s1;
+    }
+
+    /// `step` on symbols instead of bits.
+    fn step_symbols(state0: &mut SymbolicWord, state1: &mut SymbolicWord) {
+        let mut s1 = *state0;
+        let s0 = *state1;
+        *state0 = s0;
+        s1 = symbolic_xor(&s1, &symbolic_shl(&s1, 23));
+        s1 = symbolic_xor(&s1, &symbolic_shr(&s1, 17));
+        s1 = symbolic_xor(&s1, &s0);
+        s1 = symbolic_xor(&s1, &symbolic_shr(&s0, 26));
+        *state1 = s1;
+    }
+
+    /// The usual xorshift128+ output, the sum of the two halves of the state.
+    pub fn next_u64(&mut self) -> u64 {
+        self.step();
+        self.state0.wrapping_add(self.state1)
+    }
+
+    /// What V8 fills its cache with: the top 52 bits of `state0` as the fraction of a double
+    /// between one and two, less one.
+    pub fn next_v8_double(&mut self) -> f64 {
+        self.step();
+        f64::from_bits(self.state0 >> 12 | 0x3FF0000000000000) - 1.0
+    }
+
+    /// The next 64 `Math.random()` values in the order JavaScript sees them. V8 makes a cache
+    /// of them at a time and hands them out from the end.
+    pub fn next_v8_cache(&mut self) -> Vec<f64> {
+        let mut cache: Vec<f64> = (0..V8_CACHE_SIZE).map(|_| self.next_v8_double()).collect();
+        cache.reverse();
+        cache
+    }
+
+    /// Solves for the starting state. `equations` gets the symbolic state after each of
+    /// `steps` steps and says what was seen of it then, as the bits of the starting state that
+    /// XOR to each known bit.
+    fn solve_linear<F>(steps: usize, mut equations: F) -> Option<Self>
+    where
+        F: FnMut(usize, &SymbolicWord, &SymbolicWord) -> Vec<(u128, bool)>,
+    {
+        let mut state0: SymbolicWord = std::array::from_fn(|bit| 1 << bit);
+        let mut state1: SymbolicWord = std::array::from_fn(|bit| 1 << (64 + bit));
+        let mut pivots: Vec<Option<Gf2Equation>> = vec![None; 128];
+        let mut unpinned = 128;
+
+        for index in 0..steps {
+            Self::step_symbols(&mut state0, &mut state1);
+            for (bits, value) in equations(index, &state0, &state1) {
+                let row = vec![bits as u64, (bits >> 64) as u64];
+                match eliminate(&mut pivots, row, value) {
+                    Reduction::Pivot(_) => unpinned -= 1,
+                    Reduction::Contradiction => return None,
+                    Reduction::Redundant => {}
+                }
+            }
+        }
+        if unpinned > 0 {
+            return None;
+        }
+
+        let solution = back_substitute(&pivots);
+        Some(Self::from_state(solution[0], solution[1]))
+    }
+
+    /// Clones a generator from consecutive `next_v8_double` outputs, in the order they were
+    /// made. Every step is linear over GF(2), so each double's 52 bits of `state0` are
+    /// equations over the 128 bits of the starting state. The low 12 bits never show, so it
+    /// takes four doubles rather than three.
+    /// Picks up after the last one. Values seen from JavaScript come out of the cache last
+    /// first, so they need turning back around a block at a time.
+    pub fn clone_from_v8_doubles(doubles: &[f64]) -> Option<Self> {
+        let mut clone = Self::solve_linear(doubles.len(), |index, state0, _| {
+            let fraction = (doubles[index] + 1.0).to_bits();
+            (0..52)
+                .map(|bit| (state0[bit + 12], fraction >> bit & 1 == 1))
+                .collect()
+        })?;
+
+        for &double in doubles {
+            if clone.next_v8_double() != double {
+                return None;
+            }
+        }
+        Some(clone)
+    }
+
+    /// Clones a generator from consecutive `next_u64` outputs. Adding isn't linear, but the
+    /// lowest bit of a sum is the XOR of the lowest bits of what went in, so each output is
+    /// one equation and it takes a little over 128 of them.
+    pub fn clone_from_outputs(outputs: &[u64]) -> Option<Self> {
+        let mut clone = Self::solve_linear(outputs.len(), |index, state0, state1| {
+            vec![(state0[0] ^ state1[0], outputs[index] & 1 == 1)]
+        })?;
+
+        for &output in outputs {
+            if clone.next_u64() != output {
+                return None;
+            }
+        }
+        Some(clone)
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod prng_tests {
+    use std::time::Duration;
+
+    use crate::prng::*;
+    use crate::set_3::SimulatedClock;
+
+    #[test]
+    fn test_glibc_rand() {
+        let mut rng = GlibcRandom::new(1);
+        let outputs: Vec<u32> = (0..5).map(|_| rng.next()).collect();
+        assert_eq!(
+            outputs,
+            [1804289383, 846930886, 1681692777, 1714636915, 1957747793]
+        );
+
+        let mut rng = GlibcRandom::new(42);
+        assert_eq!(rng.next(), 71876166);
+        assert_eq!(rng.next(), 708592740);
+    }
+
+    #[test]
+    fn test_glibc_clone() {
+        let mut rng = GlibcRandom::new(0xBADC0DE);
+        for _ in 0..50 {
+            rng.next();
+        }
+        let outputs: Vec<u32> = (0..400).map(|_| rng.next()).collect();
+
+        let mut clone = GlibcRandom::clone_from_outputs(&outputs).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(rng.next(), clone.next());
+        }
+
+        assert!(GlibcRandom::clone_from_outputs(&outputs[..40]).is_none());
+        let mut tampered = outputs.clone();
+        tampered[200] ^= 0x100;
+        assert!(GlibcRandom::clone_from_outputs(&tampered).is_none());
+    }
+
+    #[test]
+    fn test_glibc_seed_cracker() {
+        let clock = SimulatedClock::new(Duration::from_secs(1_700_000_000));
+        let seed = SeedResolution::Seconds.seed_at(clock.now());
+        let first = GlibcRandom::new(seed).next();
+        assert_eq!(first, 280491303);
+        clock.advance(Duration::from_secs(300));
+
+        assert_eq!(
+            Some(seed),
+            crack_glibc_seed(&clock, SeedResolution::Seconds, first, 1000, 0)
+        );
+        assert_eq!(
+            None,
+            crack_glibc_seed(&clock, SeedResolution::Seconds, first, 100, 0)
+        );
+    }
+
+    #[test]
+    fn test_java_random() {
+        let mut rng = JavaRandom::new(42);
+        assert_eq!(rng.next_int(), -1170105035);
+        assert_eq!(rng.next_int(), 234785527);
+
+        let mut rng = JavaRandom::new(42);
+        let rolls: Vec<i32> = (0..5).map(|_| rng.next_int_bounded(10)).collect();
+        assert_eq!(rolls, [0, 3, 8, 4, 0]);
+
+        let mut rng = JavaRandom::new(42);
+        assert_eq!(rng.next_double(), 0.7275636800328681);
+        assert_eq!(JavaRandom::new(42).next_long(), -5025562857975149833);
+    }
+
+    #[test]
+    fn test_java_clone() {
+        let mut rng = JavaRandom::new(0x5EED);
+        let ints: Vec<i32> = (0..3).map(|_| rng.next_int()).collect();
+        let mut clone = JavaRandom::clone_from_ints(&ints).unwrap();
+        assert_eq!(rng.next_long(), clone.next_long());
+
+        // nextInt(2^24) only shows the top 24 bits
+        let tops: Vec<u64> = (0..4)
+            .map(|_| rng.next_int_bounded(1 << 24) as u64)
+            .collect();
+        let mut clone = JavaRandom::clone_from_high_bits(&tops, 24).unwrap();
+        assert_eq!(rng.next_double(), clone.next_double());
+
+        assert!(JavaRandom::clone_from_ints(&ints[..1]).is_none());
+    }
+
+    #[test]
+    fn test_java_seed_cracker() {
+        let clock = SimulatedClock::new(Duration::from_millis(1_700_000_000_123));
+        let seed = SeedResolution::Millis.count_at(clock.now()) as i64;
+        let first = JavaRandom::new(seed).next_int();
+        clock.advance(Duration::from_millis(2500));
+
+        assert_eq!(
+            Some(seed),
+            crack_java_seed(&clock, SeedResolution::Millis, first, 5000, 0)
+        );
+    }
+
+    #[test]
+    fn test_v8_math_random() {
+        // node --random_seed=42, the first three values and the three after the first cache
+        let mut rng = Xorshift128Plus::new(42);
+        let first = rng.next_v8_cache();
+        assert_eq!(
+            first[..3],
+            [0.7939112874678715, 0.5254990606499601, 0.3518347850388237]
+        );
+        assert_eq!(
+            rng.next_v8_cache()[..3],
+            [0.4706713645501157, 0.2603201442309373, 0.5279993703358514]
+        );
+
+        // the last four out of the cache were the first four made
+        let made = [
+            0.08156904043271651,
+            0.147200914094598,
+            0.5226472860357483,
+            0.3891536450053332,
+        ];
+        let mut clone = Xorshift128Plus::clone_from_v8_doubles(&made).unwrap();
+        for _ in made.len()..64 {
+            clone.next_v8_double();
+        }
+        assert_eq!(
+            clone.next_v8_cache()[..2],
+            [0.4706713645501157, 0.2603201442309373]
+        );
+
+        assert!(Xorshift128Plus::clone_from_v8_doubles(&made[..3]).is_none());
+    }
+
+    #[test]
+    fn test_xorshift_clone_from_outputs() {
+        let mut rng = Xorshift128Plus::new(0xFEEDFACE);
+        let outputs: Vec<u64> = (0..200).map(|_| rng.next_u64()).collect();
+
+        let mut clone = Xorshift128Plus::clone_from_outputs(&outputs).unwrap();
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), clone.next_u64());
+        }
+        assert!(Xorshift128Plus::clone_from_outputs(&outputs[..100]).is_none());
+    }
+}
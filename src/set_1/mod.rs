@@ -7,11 +7,14 @@ use aes::cipher::{BlockDecrypt, KeyInit};
 use aes::cipher::consts::U16;
 use aes::cipher::generic_array::GenericArray;
 
-use crate::utils::{get_english_corpus, get_score_of_english_chars, hamming_distance, transpose};
+use crate::utils::{
+    get_byte_log_likelihoods, get_english_byte_frequencies, get_english_corpus,
+    get_score_of_english_chars, hamming_distance, index_of_coincidence, transpose,
+};
 
 mod tests;
 
-type Key = String;
+type Key = Vec<u8>;
 
 
 pub fn fixed_xor(first: &[u8], second: &[u8]) -> Vec<u8> {
@@ -83,41 +86,616 @@ pub fn repeating_key_xor(cipher: &[u8], key: &[u8]) -> String {
     hex::encode(&res)
 }
 
-pub fn break_repeating_key_xor(bytes: &[u8]) -> Key {
-    let mut v: Vec<(usize, f64)> = (2..40).map(|k| {
-        let b: Vec<&[u8]> = bytes.chunks(k)
-            .take(4)
-            .collect();
+/// How likely a key size is, with the three measurements that went into it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySizeEstimate {
+    pub key_size: usize,
+    /// average Hamming distance between key-size blocks, in bits per byte. Lower is better.
+    pub hamming: f64,
+    /// average index of coincidence of the transposed columns. Higher is better.
+    pub ioc: f64,
+    /// share of the gaps between repeated trigrams that the key size divides. Higher is better.
+    pub kasiski: f64,
+    pub score: f64,
+}
+
+/// Most blocks compared against each other per key size in the Hamming measurement.
+const MAX_HAMMING_BLOCKS: usize = 64;
+
+/// Ranks every key size from 2 to `max_key_size`, most likely first.
+///
+/// Each measurement on its own has a blind spot: Hamming distance and index of coincidence both
+/// look just as good at multiples of the key size, and Kasiski gaps look even better at its
+/// divisors. Mixing columns at a divisor ruins the index of coincidence, and fewer gaps are
+/// divisible by a multiple, so the standardised scores are added up to cover both.
+pub fn estimate_key_sizes(bytes: &[u8], max_key_size: usize) -> Vec<KeySizeEstimate> {
+    let gaps = repeated_trigram_gaps(bytes);
+    let max_key_size = max_key_size.min(bytes.len() / 2);
+
+    let mut estimates: Vec<KeySizeEstimate> = (2..=max_key_size)
+        .map(|k| {
+            let blocks: Vec<&[u8]> = bytes.chunks_exact(k).take(MAX_HAMMING_BLOCKS).collect();
+            let mut distance = 0.0;
+            let mut pairs = 0;
+            for i in 0..blocks.len() {
+                for j in i + 1..blocks.len() {
+                    distance += hamming_distance(blocks[i], blocks[j]) as f64;
+                    pairs += 1;
+                }
+            }
 
-        let mut s = 0.0;
-        for i in 0..b.len() {
-            for j in i..b.len() {
-                s += hamming_distance(b[i], b[j]) as f64
+            let columns = transpose(&bytes[..bytes.len() - bytes.len() % k], k);
+            let ioc = columns.iter().map(|c| index_of_coincidence(c)).sum::<f64>() / k as f64;
+
+            let kasiski = if gaps.is_empty() {
+                0.0
+            } else {
+                gaps.iter().filter(|g| *g % k == 0).count() as f64 / gaps.len() as f64
+            };
+
+            KeySizeEstimate {
+                key_size: k,
+                hamming: distance / (pairs.max(1) * k) as f64,
+                ioc,
+                kasiski,
+                score: 0.0,
             }
+        })
+        .collect();
+
+    let hamming = standardise(&estimates.iter().map(|e| e.hamming).collect::<Vec<_>>());
+    let ioc = standardise(&estimates.iter().map(|e| e.ioc).collect::<Vec<_>>());
+    let kasiski = standardise(&estimates.iter().map(|e| e.kasiski).collect::<Vec<_>>());
+    for (i, estimate) in estimates.iter_mut().enumerate() {
+        estimate.score = ioc[i] + kasiski[i] - hamming[i];
+    }
+
+    estimates.sort_by(|e1, e2| e2.score.partial_cmp(&e1.score).unwrap());
+    estimates
+}
+
+/// Distances between consecutive occurrences of every trigram that shows up more than once.
+fn repeated_trigram_gaps(bytes: &[u8]) -> Vec<usize> {
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut gaps = vec![];
+
+    for (i, trigram) in bytes.windows(3).enumerate() {
+        if let Some(prev) = last_seen.insert(trigram, i) {
+            gaps.push(i - prev);
         }
-        let avg_distance = (s / (k as f64)) / 6f64;
-        (k, avg_distance)
-    }).collect::<Vec<(usize, f64)>>();
+    }
+    gaps
+}
+
+/// Z-scores, or all zeroes if the values don't vary.
+fn standardise(values: &[f64]) -> Vec<f64> {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
 
-    v.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap());
-    println!("{:?}", v);
+    if sd == 0.0 {
+        return vec![0.0; values.len()];
+    }
+    values.iter().map(|v| (v - mean) / sd).collect()
+}
 
-    let key_size = v.first().unwrap().0;
+/// Breaks repeating-key XOR over English text with the most likely key size up to `max_key_size`.
+/// The key itself can be any bytes. `None` when there's no key size to try, because `bytes` is
+/// under 4 bytes long or `max_key_size` is under 2.
+pub fn break_repeating_key_xor(bytes: &[u8], max_key_size: usize) -> Option<Key> {
+    let key_size = estimate_key_sizes(bytes, max_key_size).first()?.key_size;
 
-    let blocks = transpose(bytes, key_size);
-    let mut res = vec![];
     let corpus = get_english_corpus();
+    let key = transpose(bytes, key_size)
+        .iter()
+        .map(|block| single_byte_xor(block, &corpus).0)
+        .collect();
+    Some(key)
+}
 
-    (0..blocks.len()).for_each(|i| {
-        let sres = single_byte_xor(&blocks[i], &corpus);
-        res.push(sres.0);
-    });
-    let s = repeating_key_xor(&bytes, &res);
-    println!("{:?}", String::from_utf8(hex::decode(s).unwrap()));
+/// `key` repeated, with every byte of the stream stepped up by `step` more than the one before:
+/// byte `i` is `key[i % key.len()] + i * step`.
+pub fn incrementing_keystream(key: &[u8], step: u8, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| key[i % key.len()].wrapping_add((i as u8).wrapping_mul(step)))
+        .collect()
+}
+
+pub fn incrementing_key_xor(bytes: &[u8], key: &[u8], step: u8) -> Vec<u8> {
+    fixed_xor(bytes, &incrementing_keystream(key, step, bytes.len()))
+}
+
+/// Finds the key and step of `incrementing_key_xor` for a known key size.
+///
+/// Byte `r` of column `j` is XORed with `key[j] + j * step + r * key_size * step`, so every
+/// column is a single byte key plus the same ramp. With no ramp that's `single_byte_xor`,
+/// otherwise every start byte is tried against the ramp. All 256 steps are tried and the one
+/// that reads most like English wins. `None` when `key_size` is 0 or `bytes` doesn't fill a
+/// single row of it.
+pub fn break_incrementing_key_xor(bytes: &[u8], key_size: usize) -> Option<(Key, u8)> {
+    if key_size == 0 || bytes.len() < key_size {
+        return None;
+    }
+
+    let corpus = get_english_corpus();
+    let scores = get_byte_log_likelihoods(&corpus);
+    let score = |plain: &[u8]| plain.iter().map(|&b| scores[b as usize]).sum::<f64>();
+
+    // transpose pads the last row with zeros, which would throw off the scores
+    let whole_rows = &bytes[..bytes.len() - bytes.len() % key_size];
+    let columns = transpose(whole_rows, key_size);
+
+    let (key, step, _) = (0..=255u8)
+        .map(|step| {
+            let ramp = (key_size as u8).wrapping_mul(step);
+            let starts: Vec<u8> = columns
+                .iter()
+                .map(|column| {
+                    if ramp == 0 {
+                        return single_byte_xor(column, &corpus).0;
+                    }
+                    (0..=255u8)
+                        .map(|start| {
+                            let mut k = start;
+                            let mut total = 0.0;
+                            for &b in column {
+                                total += scores[(b ^ k) as usize];
+                                k = k.wrapping_add(ramp);
+                            }
+                            (start, total)
+                        })
+                        .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+                        .unwrap()
+                        .0
+                })
+                .collect();
+
+            let key: Key = starts
+                .iter()
+                .enumerate()
+                .map(|(j, start)| start.wrapping_sub((j as u8).wrapping_mul(step)))
+                .collect();
+            let plain_score = score(&incrementing_key_xor(whole_rows, &key, step));
+            (key, step, plain_score)
+        })
+        .max_by(|(_, _, s1), (_, _, s2)| s1.partial_cmp(s2).unwrap())
+        .unwrap();
+
+    Some((key, step))
+}
 
-    String::from_utf8(res).unwrap()
+/// The states of the byte-sized LCG `x = a * x + c`, starting at `seed`.
+pub fn lcg_keystream(seed: u8, a: u8, c: u8, len: usize) -> Vec<u8> {
+    let mut x = seed;
+    (0..len)
+        .map(|_| {
+            let byte = x;
+            x = a.wrapping_mul(x).wrapping_add(c);
+            byte
+        })
+        .collect()
 }
 
+pub fn lcg_xor(bytes: &[u8], seed: u8, a: u8, c: u8) -> Vec<u8> {
+    fixed_xor(bytes, &lcg_keystream(seed, a, c, bytes.len()))
+}
+
+/// How many of the first bytes of a guess may be ones English never uses before
+/// `break_lcg_xor` gives up on it.
+const LCG_ALLOWED_MISSES: usize = 2;
+
+/// Finds `(seed, a, c)` for `lcg_xor` by trying all 2^24 of them. Most guesses turn into bytes
+/// English never uses within a few characters, so those are dropped early and only the
+/// survivors get scored on the whole text. Different parameters can give the same keystream,
+/// so this returns one that decrypts the same way, not necessarily the one that was used.
+pub fn break_lcg_xor(bytes: &[u8]) -> (u8, u8, u8) {
+    let used = get_english_byte_frequencies();
+    let scores = get_byte_log_likelihoods(&get_english_corpus());
+    let prefix = &bytes[..bytes.len().min(32)];
+
+    let mut best = ((0, 0, 0), f64::NEG_INFINITY);
+    for a in 0..=255u8 {
+        for c in 0..=255u8 {
+            for seed in 0..=255u8 {
+                let mut x = seed;
+                let mut misses = 0;
+                for &b in prefix {
+                    if used[(b ^ x) as usize] == 0.0 {
+                        misses += 1;
+                        if misses > LCG_ALLOWED_MISSES {
+                            break;
+                        }
+                    }
+                    x = a.wrapping_mul(x).wrapping_add(c);
+                }
+                if misses > LCG_ALLOWED_MISSES {
+                    continue;
+                }
+
+                let score: f64 = lcg_xor(bytes, seed, a, c)
+                    .iter()
+                    .map(|&b| scores[b as usize])
+                    .sum();
+                if score > best.1 {
+                    best = ((seed, a, c), score);
+                }
+            }
+        }
+    }
+
+    best.0
+}
+
+/// XOR with a repeating key and the previous cipher text byte, starting from zero.
+pub fn chained_xor_encrypt(bytes: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut previous = 0;
+    bytes
+        .iter()
+        .zip(key.iter().cycle())
+        .map(|(b, k)| {
+            previous ^= b ^ k;
+            previous
+        })
+        .collect()
+}
+
+pub fn chained_xor_decrypt(bytes: &[u8], key: &[u8]) -> Vec<u8> {
+    unchain(bytes)
+        .iter()
+        .zip(key.iter().cycle())
+        .map(|(b, k)| b ^ k)
+        .collect()
+}
+
+/// Each cipher text byte XORed with the one before it, which leaves plain repeating-key XOR.
+fn unchain(bytes: &[u8]) -> Vec<u8> {
+    let mut previous = 0;
+    bytes
+        .iter()
+        .map(|&b| {
+            let unchained = b ^ previous;
+            previous = b;
+            unchained
+        })
+        .collect()
+}
+
+/// The cipher text itself is the rest of the keystream, so undoing the chaining leaves
+/// nothing but `break_repeating_key_xor`.
+pub fn break_chained_xor(bytes: &[u8], max_key_size: usize) -> Option<Key> {
+    break_repeating_key_xor(&unchain(bytes), max_key_size)
+}
+
+/// Where a crib sits in a file: so many bytes from the start, or its last byte so many bytes
+/// before the end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CribPosition {
+    Start(usize),
+    End(usize),
+}
+
+/// Bytes a file format always has in the same place.
+pub struct Crib {
+    pub position: CribPosition,
+    pub bytes: &'static [u8],
+}
+
+/// A file format we can recognise: the bytes it always has, the byte its contents are mostly
+/// made of (to guess key bytes no crib covers), and how well a decryption parses as it, from
+/// 0 to 1.
+pub struct FileFormat {
+    pub name: &'static str,
+    pub cribs: Vec<Crib>,
+    pub filler: u8,
+    pub parse: fn(&[u8]) -> f64,
+}
+
+/// A repeating XOR key guessed from a format's cribs.
+#[derive(Debug)]
+pub struct CribKeyCandidate {
+    pub format: &'static str,
+    pub key: Key,
+    pub parse_score: f64,
+}
+
+pub fn known_file_formats() -> Vec<FileFormat> {
+    vec![
+        FileFormat {
+            name: "png",
+            cribs: vec![
+                Crib {
+                    position: CribPosition::Start(0),
+                    bytes: b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR",
+                },
+                Crib {
+                    position: CribPosition::End(0),
+                    bytes: b"\x00\x00\x00\x00IEND\xae\x42\x60\x82",
+                },
+            ],
+            filler: 0,
+            parse: parse_png,
+        },
+        FileFormat {
+            name: "zip",
+            cribs: vec![
+                Crib {
+                    position: CribPosition::Start(0),
+                    bytes: b"PK\x03\x04",
+                },
+                // the end of central directory record of an archive with one disk and no comment
+                Crib {
+                    position: CribPosition::End(14),
+                    bytes: b"PK\x05\x06\x00\x00\x00\x00",
+                },
+            ],
+            filler: 0,
+            parse: parse_zip,
+        },
+        FileFormat {
+            name: "pdf",
+            cribs: vec![
+                Crib {
+                    position: CribPosition::Start(0),
+                    bytes: b"%PDF-1.",
+                },
+                Crib {
+                    position: CribPosition::End(1),
+                    bytes: b"%%EOF",
+                },
+            ],
+            filler: b' ',
+            parse: parse_pdf,
+        },
+        FileFormat {
+            name: "elf",
+            cribs: vec![
+                Crib {
+                    position: CribPosition::Start(0),
+                    bytes: b"\x7fELF",
+                },
+                // version, then the OS ABI, ABI version and padding, all zero on Linux
+                Crib {
+                    position: CribPosition::Start(6),
+                    bytes: b"\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00",
+                },
+            ],
+            filler: 0,
+            parse: parse_elf,
+        },
+    ]
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn read_u16_le(bytes: &[u8], at: usize) -> Option<usize> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u16::from_le_bytes([b[0], b[1]]) as usize)
+}
+
+fn read_u32_le(bytes: &[u8], at: usize) -> Option<usize> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+/// Share of the file covered by chunks with a good CRC, walking from the signature.
+fn parse_png(bytes: &[u8]) -> f64 {
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return 0.0;
+    }
+
+    let mut at = 8;
+    while let Some(len) = bytes.get(at..at + 4) {
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        let Some(chunk) = bytes.get(at + 4..at + 8 + len) else {
+            break;
+        };
+        let Some(crc) = bytes.get(at + 8 + len..at + 12 + len) else {
+            break;
+        };
+        if crc32(chunk) != u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+            break;
+        }
+        at += 12 + len;
+    }
+
+    at as f64 / bytes.len() as f64
+}
+
+/// Share of the file covered by local file headers with their data, then central directory
+/// headers, then the end record.
+fn parse_zip(bytes: &[u8]) -> f64 {
+    // a record only counts if it fits in what's left of the file
+    let fits = |next: usize| next <= bytes.len();
+
+    let mut at = 0;
+    while bytes.get(at..at + 4) == Some(b"PK\x03\x04") {
+        let (Some(size), Some(name), Some(extra)) = (
+            read_u32_le(bytes, at + 18),
+            read_u16_le(bytes, at + 26),
+            read_u16_le(bytes, at + 28),
+        ) else {
+            break;
+        };
+        if !fits(at + 30 + name + extra + size) {
+            break;
+        }
+        at += 30 + name + extra + size;
+    }
+    while bytes.get(at..at + 4) == Some(b"PK\x01\x02") {
+        let (Some(name), Some(extra), Some(comment)) = (
+            read_u16_le(bytes, at + 28),
+            read_u16_le(bytes, at + 30),
+            read_u16_le(bytes, at + 32),
+        ) else {
+            break;
+        };
+        if !fits(at + 46 + name + extra + comment) {
+            break;
+        }
+        at += 46 + name + extra + comment;
+    }
+    if bytes.get(at..at + 4) == Some(b"PK\x05\x06") {
+        if let Some(comment) = read_u16_le(bytes, at + 20) {
+            if fits(at + 22 + comment) {
+                at += 22 + comment;
+            }
+        }
+    }
+
+    at as f64 / bytes.len() as f64
+}
+
+/// Half for the header and trailer, half for how much of it is text. Streams can be binary,
+/// but the rest of a PDF isn't.
+fn parse_pdf(bytes: &[u8]) -> f64 {
+    let framed = bytes.starts_with(b"%PDF-1.")
+        && bytes[bytes.len().saturating_sub(32)..]
+            .windows(5)
+            .any(|w| w == b"%%EOF");
+    let text = bytes
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        .count();
+
+    let framing = if framed { 0.5 } else { 0.0 };
+    framing + 0.5 * text as f64 / bytes.len() as f64
+}
+
+/// Share of the header fields that hold values they're allowed to.
+fn parse_elf(bytes: &[u8]) -> f64 {
+    if !bytes.starts_with(b"\x7fELF") || bytes.len() < 52 {
+        return 0.0;
+    }
+
+    let wide = bytes[4] == 2;
+    let read_u16 = |at: usize| {
+        let b = [bytes[at], bytes[at + 1]];
+        if bytes[5] == 2 {
+            u16::from_be_bytes(b)
+        } else {
+            u16::from_le_bytes(b)
+        }
+    };
+    let (header_size, program_entry, section_entry) =
+        if wide { (64, 56, 64) } else { (52, 32, 40) };
+    let header_at = if wide { 52 } else { 40 };
+    if bytes.len() < header_at + 12 {
+        return 0.0;
+    }
+
+    let checks = [
+        bytes[4] == 1 || bytes[4] == 2,
+        bytes[5] == 1 || bytes[5] == 2,
+        bytes[6] == 1,
+        (1..=4).contains(&read_u16(16)),
+        bytes[20..24] == if bytes[5] == 2 { [0, 0, 0, 1] } else { [1, 0, 0, 0] },
+        bytes[7..16].iter().all(|&b| b == 0),
+        read_u16(header_at) == header_size,
+        read_u16(header_at + 2) == program_entry || read_u16(header_at + 4) == 0,
+        read_u16(header_at + 6) == section_entry || read_u16(header_at + 8) == 0,
+    ];
+    checks.iter().filter(|&&ok| ok).count() as f64 / checks.len() as f64
+}
+
+/// Known-plaintext take on `break_repeating_key_xor` for when all we know is what kind of file
+/// was encrypted.
+///
+/// Every crib gives key bytes through `fixed_xor` with the cipher text under it. A period is
+/// only kept if every crib byte that lands on the same key byte agrees. Key bytes no crib
+/// reaches are guessed by assuming the most common cipher byte in that column is the format's
+/// filler. The candidates are then ranked by how well the whole decryption parses, with shorter
+/// keys first on a tie.
+pub fn break_repeating_key_xor_with_cribs(
+    bytes: &[u8],
+    formats: &[FileFormat],
+    max_key_size: usize,
+) -> Vec<CribKeyCandidate> {
+    let mut candidates = vec![];
+
+    for format in formats {
+        // (position in the file, key byte there)
+        let mut known: Vec<(usize, u8)> = vec![];
+        for crib in &format.cribs {
+            let start = match crib.position {
+                CribPosition::Start(offset) => offset,
+                CribPosition::End(offset) => {
+                    match bytes.len().checked_sub(offset + crib.bytes.len()) {
+                        Some(start) => start,
+                        None => continue,
+                    }
+                }
+            };
+            let Some(under) = bytes.get(start..start + crib.bytes.len()) else {
+                continue;
+            };
+            let key_bytes = fixed_xor(under, crib.bytes);
+            known.extend(
+                key_bytes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, k)| (start + i, k)),
+            );
+        }
+        if known.is_empty() {
+            continue;
+        }
+
+        for key_size in 1..=max_key_size.min(bytes.len()) {
+            let mut key: Vec<Option<u8>> = vec![None; key_size];
+            let consistent = known.iter().all(|&(at, k)| {
+                let slot = &mut key[at % key_size];
+                *slot.get_or_insert(k) == k
+            });
+            if !consistent {
+                continue;
+            }
+
+            let key: Key = key
+                .iter()
+                .enumerate()
+                .map(|(i, k)| {
+                    k.unwrap_or_else(|| {
+                        let mut counts = [0usize; 256];
+                        for b in bytes.iter().skip(i).step_by(key_size) {
+                            counts[*b as usize] += 1;
+                        }
+                        let most_common = (0..=255u8).max_by_key(|b| counts[*b as usize]).unwrap();
+                        most_common ^ format.filler
+                    })
+                })
+                .collect();
+
+            let plain: Vec<u8> = bytes
+                .iter()
+                .zip(key.iter().cycle())
+                .map(|(b, k)| b ^ k)
+                .collect();
+            candidates.push(CribKeyCandidate {
+                format: format.name,
+                parse_score: (format.parse)(&plain),
+                key,
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| {
+        b.parse_score
+            .partial_cmp(&a.parse_score)
+            .unwrap()
+            .then(a.key.len().cmp(&b.key.len()))
+    });
+    candidates
+}
 
 pub fn decrypt_aes_ecb(key_stream: &[u8], text_stream: &[u8]) -> Vec<u8> {
     let key = GenericArray::from_slice(key_stream);
@@ -137,7 +715,7 @@ fn detect_ecb() -> Option<String> {
     let mut res = None;
     let f = fs::read_to_string("texts/8.txt").unwrap();
 
-    for (_i, line) in f.lines().enumerate() {
+    for line in f.lines() {
         let decoded = hex::decode(line).unwrap();
         let chunked: Vec<_> = decoded.chunks(16).collect();
         let set: HashSet<_> = HashSet::from_iter(&chunked);
@@ -3,6 +3,8 @@ mod set1_tests {
     use crate::set_1::*;
     use base64::engine::general_purpose;
     use base64::Engine;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
     use std::fs;
 
     #[test]
@@ -63,8 +65,165 @@ I go crazy when I hear a cymbal"
         // println!("{}", text);
         let bytes = general_purpose::STANDARD.decode(text.trim()).unwrap();
 
-        let key = break_repeating_key_xor(&bytes);
-        assert_eq!("Terminator X: Bring the noise", key);
+        let key = break_repeating_key_xor(&bytes, 40);
+        assert_eq!(Some(b"Terminator X: Bring the noise".to_vec()), key);
+    }
+
+    #[test]
+    fn test_breaking_repeating_key_xor_needs_a_key_size() {
+        assert_eq!(None, break_repeating_key_xor(b"", 40));
+        assert_eq!(None, break_repeating_key_xor(b"abc", 40));
+        assert_eq!(None, break_repeating_key_xor(b"a longer cipher text", 1));
+        assert!(break_repeating_key_xor(b"abcd", 40).is_some());
+    }
+
+    #[test]
+    fn test_breaking_long_binary_repeating_key_xor() {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        let plain_text = &text.as_bytes()[20000..30000];
+        let key: Vec<u8> = (0..53u8).map(|i| i.wrapping_mul(157) ^ 0xA5).collect();
+        let cipher = hex::decode(repeating_key_xor(plain_text, &key)).unwrap();
+
+        let estimates = estimate_key_sizes(&cipher, 120);
+        assert_eq!(53, estimates[0].key_size);
+        assert_eq!(Some(key), break_repeating_key_xor(&cipher, 120));
+    }
+
+    #[test]
+    fn test_breaking_incrementing_key_xor() {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        let plain_text = &text.as_bytes()[40000..41000];
+
+        let cipher = incrementing_key_xor(plain_text, b"k3y", 7);
+        assert_eq!(plain_text, incrementing_key_xor(&cipher, b"k3y", 7));
+        assert_eq!(
+            Some((b"k3y".to_vec(), 7)),
+            break_incrementing_key_xor(&cipher, 3)
+        );
+
+        // no step is just repeating-key XOR
+        let repeating = hex::decode(repeating_key_xor(plain_text, b"k3y")).unwrap();
+        assert_eq!(repeating, incrementing_key_xor(plain_text, b"k3y", 0));
+        assert_eq!(
+            Some((b"k3y".to_vec(), 0)),
+            break_incrementing_key_xor(&repeating, 3)
+        );
+
+        assert_eq!(None, break_incrementing_key_xor(&cipher, 0));
+        assert_eq!(None, break_incrementing_key_xor(&cipher[..2], 3));
+    }
+
+    #[test]
+    fn test_breaking_lcg_xor() {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        let plain_text = &text.as_bytes()[50000..50500];
+
+        let cipher = lcg_xor(plain_text, 0x42, 0x4d, 0x1b);
+        let (seed, a, c) = break_lcg_xor(&cipher);
+        assert_eq!(plain_text, lcg_xor(&cipher, seed, a, c));
+    }
+
+    #[test]
+    fn test_breaking_chained_xor() {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        let plain_text = &text.as_bytes()[60000..63000];
+
+        let cipher = chained_xor_encrypt(plain_text, b"ICE ICE BABY");
+        assert_eq!(plain_text, chained_xor_decrypt(&cipher, b"ICE ICE BABY"));
+        assert_eq!(Some(b"ICE ICE BABY".to_vec()), break_chained_xor(&cipher, 40));
+    }
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&crc32(&chunk[4..]).to_be_bytes());
+        chunk
+    }
+
+    #[test]
+    fn test_crib_key_recovery_png() {
+        let mut rng = SmallRng::seed_from_u64(40);
+        let mut image_data = vec![0u8; 2000];
+        rng.fill(&mut image_data[..]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(
+            b"IHDR",
+            &[0, 0, 0, 32, 0, 0, 0, 32, 8, 6, 0, 0, 0],
+        ));
+        png.extend(png_chunk(b"IDAT", &image_data));
+        png.extend(png_chunk(b"IEND", &[]));
+
+        let key = b"not so secret".to_vec();
+        let cipher = hex::decode(repeating_key_xor(&png, &key)).unwrap();
+
+        let candidates = break_repeating_key_xor_with_cribs(&cipher, &known_file_formats(), 40);
+        assert_eq!("png", candidates[0].format);
+        assert_eq!(key, candidates[0].key);
+        assert_eq!(1.0, candidates[0].parse_score);
+    }
+
+    #[test]
+    fn test_crib_key_recovery_elf() {
+        let mut elf = vec![0u8; 4096];
+        elf[..16].copy_from_slice(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00");
+        elf[16..20].copy_from_slice(&[2, 0, 0x3e, 0]);
+        elf[20..24].copy_from_slice(&[1, 0, 0, 0]);
+        elf[52..62].copy_from_slice(&[64, 0, 56, 0, 1, 0, 64, 0, 0, 0]);
+
+        // a sprinkling of code and data among the zeros
+        let mut rng = SmallRng::seed_from_u64(40);
+        for b in elf[64..].iter_mut() {
+            if rng.gen_bool(0.3) {
+                *b = rng.gen();
+            }
+        }
+
+        let key: Vec<u8> = (0..24u8).map(|i| i.wrapping_mul(73) ^ 0x5c).collect();
+        let cipher = hex::decode(repeating_key_xor(&elf, &key)).unwrap();
+
+        let candidates = break_repeating_key_xor_with_cribs(&cipher, &known_file_formats(), 40);
+        assert_eq!("elf", candidates[0].format);
+        assert_eq!(key, candidates[0].key);
+    }
+
+    #[test]
+    fn test_crib_key_recovery_zip() {
+        let text = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+        let contents = &text.as_bytes()[..3000];
+        let name = b"pride.txt";
+
+        // one stored file, its central directory entry and the end record
+        let mut local = b"PK\x03\x04\x0a\x00\x00\x00\x00\x00".to_vec();
+        local.extend_from_slice(&[0; 4]);
+        local.extend_from_slice(&crc32(contents).to_le_bytes());
+        local.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        local.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        local.extend_from_slice(&[0, 0]);
+        local.extend_from_slice(name);
+        local.extend_from_slice(contents);
+
+        let mut central = b"PK\x01\x02\x14\x00".to_vec();
+        central.extend_from_slice(&local[4..30]);
+        central.extend_from_slice(&[0; 14]);
+        central.extend_from_slice(name);
+
+        let mut zip = local.clone();
+        zip.extend_from_slice(&central);
+        zip.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00\x01\x00\x01\x00");
+        zip.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(local.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+
+        let key = b"zipkey".to_vec();
+        let cipher = hex::decode(repeating_key_xor(&zip, &key)).unwrap();
+
+        let candidates = break_repeating_key_xor_with_cribs(&cipher, &known_file_formats(), 40);
+        assert_eq!("zip", candidates[0].format);
+        assert_eq!(key, candidates[0].key);
+        assert_eq!(1.0, candidates[0].parse_score);
     }
 
     #[test]
@@ -95,6 +95,20 @@ fn generate_random_aes_key() -> [u8; 16] {
 fn encryption_oracle(plain_text: &[u8]) -> (Vec<u8>, EncryptionType) {
     let key = generate_random_aes_key();
     let mut rng = SmallRng::from_rng(thread_rng()).unwrap();
+    encrypt_with_random_mode(&mut rng, key, plain_text)
+}
+
+/// `encryption_oracle` with every random choice, the key included, taken from `rng`.
+fn encryption_oracle_with<R: RngCore>(rng: &mut R, plain_text: &[u8]) -> (Vec<u8>, EncryptionType) {
+    let key: [u8; 16] = rng.gen();
+    encrypt_with_random_mode(rng, key, plain_text)
+}
+
+fn encrypt_with_random_mode<R: RngCore>(
+    rng: &mut R,
+    key: [u8; 16],
+    plain_text: &[u8],
+) -> (Vec<u8>, EncryptionType) {
     let pad_size = rng.gen_range(5..=10);
     let mut before: [u8; 10] = [0; 10];
     let mut after: [u8; 10] = [0; 10];
@@ -281,7 +295,7 @@ pub(crate) fn ecb_cut_and_paste() -> bool {
 
 pub fn has_padding(plain_text: &[u8]) -> bool {
     let last_byte = *plain_text.last().unwrap() as usize;
-    if plain_text.len() < last_byte || last_byte <= 0 { return  false }
+    if plain_text.len() < last_byte || last_byte == 0 { return  false }
 
     let rem = plain_text.len() - last_byte;
 
@@ -292,7 +306,7 @@ pub fn has_padding(plain_text: &[u8]) -> bool {
     true
 }
 
-fn strip_padding(plain_text: &[u8]) -> Vec<u8> {
+pub(crate) fn strip_padding(plain_text: &[u8]) -> Vec<u8> {
     if !has_padding(plain_text) {
         panic!("Plain text has invalid PKCS#7 padding applied")
     }
@@ -306,7 +320,7 @@ fn strip_padding(plain_text: &[u8]) -> Vec<u8> {
         .collect()
 }
 
-struct CbcEncryptionOracle {
+pub(crate) struct CbcEncryptionOracle {
     key: [u8; 16],
     iv: [u8; 16],
 }
@@ -321,31 +335,96 @@ impl CbcEncryptionOracle {
     }
 
     pub fn encrypt(&self, payload: &str) -> Vec<u8> {
-        let text = payload.replace([';', '='], "_");
-
-        let s = format!(
-            "{}{}{}",
-            "comment1=cooking%20MCs;userdata=", text, ";comment2=%20like%20a%20pound%20of%20bacon"
-        );
-
+        let s = userdata_cookie(payload);
         cbc_encryption(&self.key, s.as_bytes(), &self.iv)
     }
 }
 
+/// Characters that get replaced before the userdata is embedded in the cookie.
+const SANITIZED: [char; 2] = [';', '='];
+
+/// Whether `userdata_cookie` would quote `byte` out of the payload.
+pub(crate) fn is_sanitized(byte: u8) -> bool {
+    SANITIZED.contains(&(byte as char))
+}
+
+/// Quotes out `;` and `=` and wraps the payload in the comment cookie used by the bit-flipping oracles.
+pub(crate) fn userdata_cookie(payload: &str) -> String {
+    let text = payload.replace(SANITIZED, "_");
+
+    format!(
+        "{}{}{}",
+        "comment1=cooking%20MCs;userdata=", text, ";comment2=%20like%20a%20pound%20of%20bacon"
+    )
+}
+
+struct Injection {
+    cipher: Vec<u8>,
+    /// indices of the blocks that decrypt to garbage because their cipher text was flipped
+    garbled_blocks: Vec<usize>,
+}
+
 struct CbcAttacker;
 
 impl CbcAttacker {
     pub fn make_admin(&self, oracle: &CbcEncryptionOracle) -> Vec<u8> {
-        let payload = "AAAAA:admin<true";
-        let cipher = oracle.encrypt(payload);
-        let mut cipher_blocks: Vec<_> = cipher.chunks(16).map(|chunk| chunk.to_vec()).collect();
-        // println!("Cipher blocks before: {:?}", &cipher_blocks);
-        let target_block = cipher_blocks.get_mut(1).unwrap();
-        println!("Target block before: {:?}", &target_block);
-        target_block[5] ^= 1;
-        target_block[11] ^= 1;
-
-        cipher_blocks.concat()
+        // "comment1=cooking%20MCs;userdata=" is exactly two blocks, so the second one gets sacrificed
+        self.inject(oracle, 32, b";admin=true;", 0).unwrap().cipher
+    }
+
+    /// Makes `desired_plain_text` show up `offset` bytes into the userdata.
+    ///
+    /// Bytes the oracle would sanitize (and anything that isn't ASCII) are sent as an `A` and
+    /// flipped into place through the previous cipher block, which garbles that block. Returns
+    /// `None` when a garbled block would land on the injected bytes or when a flip would need
+    /// to go into the IV.
+    pub fn inject(
+        &self,
+        oracle: &CbcEncryptionOracle,
+        known_prefix_len: usize,
+        desired_plain_text: &[u8],
+        offset: usize,
+    ) -> Option<Injection> {
+        let start = known_prefix_len + offset;
+        let end = start + desired_plain_text.len();
+
+        let mut payload = vec![b'A'; offset];
+        let mut flips = vec![];
+        for (i, &b) in desired_plain_text.iter().enumerate() {
+            if b.is_ascii() && !is_sanitized(b) {
+                payload.push(b);
+            } else {
+                payload.push(b'A');
+                flips.push((start + i, b'A' ^ b));
+            }
+        }
+
+        let payload = String::from_utf8(payload).unwrap();
+        let mut cipher = oracle.encrypt(&payload);
+        let mut garbled_blocks = vec![];
+
+        for (position, mask) in flips {
+            let block = position / 16;
+            if block == 0 {
+                return None;
+            }
+
+            let sacrificed = block - 1;
+            let sacrificed_start = sacrificed * 16;
+            if sacrificed_start < end && start < sacrificed_start + 16 {
+                return None;
+            }
+
+            cipher[position - 16] ^= mask;
+            if !garbled_blocks.contains(&sacrificed) {
+                garbled_blocks.push(sacrificed);
+            }
+        }
+
+        Some(Injection {
+            cipher,
+            garbled_blocks,
+        })
     }
 
     pub fn check_is_admin(&self, oracle: &CbcEncryptionOracle, cipher: &[u8]) -> bool {
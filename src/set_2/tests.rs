@@ -1,5 +1,3 @@
-use crate::set_2::{detect_block_cipher_mode, encrypt_aes_ecb, encryption_oracle};
-
 #[cfg(test)]
 mod set_2_tests {
     use std::collections::HashMap;
@@ -12,7 +10,11 @@ mod set_2_tests {
         byte_at_a_time_ecb_detection, cbc_decryption, ecb_cut_and_paste, has_padding, parser, pkcs7_padding, profile_for, strip_padding, CbcAttacker, CbcEncryptionOracle
     };
 
-    use super::{detect_block_cipher_mode, encrypt_aes_ecb, encryption_oracle};
+    use crate::set_2::{
+        detect_block_cipher_mode, encrypt_aes_ecb, encryption_oracle, encryption_oracle_with,
+    };
+    use crate::set_3::MersenneTwisterRNG;
+    use rand::SeedableRng;
 
     #[test]
     fn test_pkcs7_padding() {
@@ -56,7 +58,7 @@ mod set_2_tests {
         let first_line = "I'm back and I'm ringin' the bell".to_string();
         let second_line = "A rockin' on the mike while the fly girls yell".to_string();
         let third_line = "In ecstasy in the back of me".to_string();
-        let last_line = "Play that funky music";
+        let _last_line = "Play that funky music";
 
         let res = String::from_utf8(decrypted).unwrap();
         let res_lines: Vec<&str> = res.trim().lines().map(|l| l.trim()).collect();
@@ -65,7 +67,7 @@ mod set_2_tests {
         assert_eq!(first_line, *res_lines.first().unwrap());
         assert_eq!(second_line, *res_lines.get(1).unwrap());
         assert_eq!(third_line, *res_lines.get(2).unwrap());
-        // assert_eq!(*res_lines.last().unwrap(), last_line);
+        // assert_eq!(*res_lines.last().unwrap(), _last_line);
     }
 
     #[test]
@@ -95,6 +97,18 @@ Play that funky music";
         assert_eq!(oracle_encryption, detection_res)
     }
 
+    #[test]
+    fn test_encryption_oracle_with_seeded_rng() {
+        let text = [b'A'; 64];
+        for seed in 0..20 {
+            let mut rng = MersenneTwisterRNG::seed_from_u64(seed);
+            let first = encryption_oracle_with(&mut rng, &text);
+            let mut replay = MersenneTwisterRNG::seed_from_u64(seed);
+            assert_eq!(encryption_oracle_with(&mut replay, &text), first);
+            assert_eq!(detect_block_cipher_mode(&first.0), first.1);
+        }
+    }
+
     #[test]
     fn test_block_size() {
         assert!(
@@ -174,4 +188,27 @@ Play that funky music";
         let encrypted = attacker.make_admin(&oracle);
         assert!(attacker.check_is_admin(&oracle, &encrypted))
     }
+
+    #[test]
+    fn test_multi_block_injection() {
+        let oracle = CbcEncryptionOracle::new();
+        let attacker = CbcAttacker;
+        let desired = b";admin=true;\xff\x00 and a tail that spills into the next block";
+
+        let injection = attacker.inject(&oracle, 32, desired, 16).unwrap();
+        assert_eq!(vec![2], injection.garbled_blocks);
+
+        let decrypted = cbc_decryption(&oracle.key, &injection.cipher, &oracle.iv);
+        assert_eq!(desired.as_slice(), &decrypted[48..48 + desired.len()]);
+    }
+
+    #[test]
+    fn test_injection_cannot_garble_itself() {
+        let oracle = CbcEncryptionOracle::new();
+        let attacker = CbcAttacker;
+
+        // the `=` in the second block would need the first block of the injection flipped
+        let desired = b"role:admin; the second block=yes";
+        assert!(attacker.inject(&oracle, 32, desired, 16).is_none());
+    }
 }
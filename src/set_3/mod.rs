@@ -3,21 +3,43 @@
 mod tests;
 
 use std::{
-    fs, num,
+    cell::Cell,
+    collections::HashMap,
+    ops::{BitAnd, BitOr, BitXor, Not},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
     thread::{self, sleep},
     time::{Duration, Instant, SystemTime},
 };
 
 use crate::{
-    set_1::{fixed_xor, single_byte_xor},
+    set_1::fixed_xor,
     set_2::encrypt_aes_ecb,
-    utils::{get_english_corpus, transpose},
+    utils::{
+        back_substitute, eliminate, flip_row_bit, get_case_insensitive_corpus, get_english_corpus,
+        get_log_likelihood_of_english, row_bit, xor_rows, Gf2Equation, Gf2Row, Reduction,
+    },
 };
 use base64::{engine::general_purpose, Engine};
-use rand::{thread_rng, Rng};
+use rand::{rngs::SmallRng, thread_rng, Rng, RngCore, SeedableRng};
+use regex::Regex;
 
-use crate::set_2::{cbc_decryption, cbc_encryption, has_padding};
-use crate::utils::get_uppercase_corpus;
+use crate::set_2::{cbc_decryption, cbc_encryption, has_padding, pkcs7_padding, strip_padding};
+
+const PADDING_ORACLE_PLAIN_TEXTS: [&str; 10] = [
+    "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
+    "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
+    "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
+    "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
+    "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
+    "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==",
+    "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
+    "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
+    "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
+    "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
+];
 
 struct PaddingOracle {
     key: [u8; 16],
@@ -26,8 +48,11 @@ struct PaddingOracle {
 
 impl PaddingOracle {
     pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
+        Self::with_rng(&mut rand::thread_rng())
+    }
 
+    /// Draws the key and IV from `rng`.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> Self {
         Self {
             key: rng.gen(),
             iv: rng.gen(),
@@ -36,293 +61,1758 @@ impl PaddingOracle {
 
     fn pick_random_plain_text(&self) -> Vec<u8> {
         let mut rng = thread_rng();
-        let rand_idx = rng.gen_range(0..10);
-        let ciphers = [
-            "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
-            "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
-            "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
-            "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
-            "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
-            "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==",
-            "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
-            "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
-            "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
-            "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
-        ];
-        let selected_cipher = ciphers[rand_idx];
+        let rand_idx = rng.gen_range(0..PADDING_ORACLE_PLAIN_TEXTS.len());
+        let selected_cipher = PADDING_ORACLE_PLAIN_TEXTS[rand_idx];
 
         general_purpose::STANDARD.decode(selected_cipher).unwrap()
     }
 
-    pub fn encrypt(&self, plain_text: &[u8]) -> Vec<u8> {
-        // let plain_text = self.pick_random_plain_text();
-        cbc_encryption(&self.key, plain_text, &self.iv)
+    /// Returns the cipher text along with the IV it was encrypted under.
+    pub fn encrypt(&self, plain_text: &[u8]) -> (Vec<u8>, [u8; 16]) {
+        (cbc_encryption(&self.key, plain_text, &self.iv), self.iv)
     }
 
     pub fn decrypt(&self, cipher: &[u8], iv: &[u8]) -> Option<Vec<u8>> {
         let decrypted = cbc_decryption(&self.key, cipher, iv);
-        // println!("Decrypted: {:?}", &s);
-        // println!("IV: {:?}", iv);
 
         if has_padding(&decrypted) {
             return Some(decrypted);
         }
         None
     }
+
+    pub fn is_padding_valid(&self, iv: &[u8], cipher: &[u8]) -> bool {
+        self.decrypt(cipher, iv).is_some()
+    }
+}
+
+/// Limits for `PaddingOracleAttacker::attack_parallel`. `None` means unlimited.
+struct PaddingAttackBudget {
+    max_queries: Option<usize>,
+    time_limit: Option<Duration>,
+}
+
+struct PaddingAttackReport {
+    plain_text: Vec<u8>,
+    queries_per_block: Vec<usize>,
+}
+
+impl PaddingAttackReport {
+    pub fn total_queries(&self) -> usize {
+        self.queries_per_block.iter().sum()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PaddingAttackError {
+    QueryBudgetExhausted,
+    TimedOut,
+    /// no byte value gave valid padding, so the oracle isn't behaving like one
+    OracleNeverAccepted,
+    InvalidPadding,
+    /// the cipher text is empty or not whole blocks, or the IV isn't a block
+    NotWholeBlocks,
+}
+
+/// Whether `iv` and `cipher` are the shape a CBC padding oracle can work on at all.
+fn is_whole_blocks(iv: &[u8], cipher: &[u8]) -> bool {
+    iv.len() == 16 && !cipher.is_empty() && cipher.len() % 16 == 0
+}
+
+/// Every byte value, most likely plain text first according to the character frequencies in `corpus`.
+fn plain_text_prior(corpus: &HashMap<char, f64>) -> Vec<u8> {
+    let mut ranked: Vec<(u8, f64)> = (0x00u8..=0xFF)
+        .map(|b| (b, corpus.get(&(b as char)).copied().unwrap_or(0.0)))
+        .collect();
+    ranked.sort_by(|(_, f1), (_, f2)| f2.partial_cmp(f1).unwrap());
+
+    ranked.into_iter().map(|(b, _)| b).collect()
 }
 
 struct PaddingOracleAttacker;
 
 impl PaddingOracleAttacker {
-    pub fn attack(&self, oracle: &PaddingOracle, c: &[u8]) -> String {
-        let v: Vec<Vec<_>> = c
-            .chunks(16)
-            .map(|chunk| {
-                let mut res: Vec<u8> = vec![0u8; 16];
-                let mut count = 1;
-                let mut padding = [0u8; 16];
-
-                for idx in (0..chunk.len()).rev() {
-                    if count >= 2 {
-                        for i in idx + 1..chunk.len() {
-                            padding[i] = res[i] ^ count
-                        }
-                    }
+    /// Decrypts `cipher` using nothing but a padding oracle that answers for an `(iv, cipher)` pair.
+    /// Returns `None` if `cipher` isn't whole blocks, the oracle never accepts a guess or the
+    /// result isn't padded.
+    pub fn attack<F>(&self, oracle: F, iv: &[u8], cipher: &[u8]) -> Option<Vec<u8>>
+    where
+        F: Fn(&[u8], &[u8]) -> bool,
+    {
+        if !is_whole_blocks(iv, cipher) {
+            return None;
+        }
 
-                    count += 1;
-                    for b in 0x00u8..=0xFF {
-                        padding[idx] = b;
-                        if let Some(plain_text) = oracle.decrypt(chunk, &padding) {
-                            res[idx] = plain_text[idx] ^ b;
-                            break;
-                        }
+        let mut res = vec![];
+        let mut prev = iv;
+
+        for block in cipher.chunks(16) {
+            let intermediate = self.recover_intermediate(&oracle, block)?;
+            res.extend(fixed_xor(prev, &intermediate));
+            prev = block;
+        }
+
+        if !has_padding(&res) {
+            return None;
+        }
+        Some(strip_padding(&res))
+    }
+
+    /// Recovers the block cipher decryption of `block` (before it gets XORed with the previous
+    /// block) by forging IVs that give it valid padding, one byte at a time from the end.
+    pub fn recover_intermediate<F>(&self, oracle: &F, block: &[u8]) -> Option<[u8; 16]>
+    where
+        F: Fn(&[u8], &[u8]) -> bool,
+    {
+        let candidates: Vec<u8> = (0x00u8..=0xFF).collect();
+        self.recover_intermediate_with(oracle, block, &[0; 16], &candidates, 1)
+    }
+
+    /// Same as `recover_intermediate`, but tries the plain text bytes in `candidates` order.
+    /// `prev` is the block that gets XORed in after decryption, which is what turns a plain text
+    /// guess into a forged byte. A byte nobody accepts gets up to `sweeps` passes over the
+    /// candidates, in case the oracle lied about the right one.
+    fn recover_intermediate_with<F>(
+        &self,
+        oracle: &F,
+        block: &[u8],
+        prev: &[u8],
+        candidates: &[u8],
+        sweeps: usize,
+    ) -> Option<[u8; 16]>
+    where
+        F: Fn(&[u8], &[u8]) -> bool,
+    {
+        let mut intermediate = [0u8; 16];
+
+        for pad in 1..=16u8 {
+            let idx = 16 - pad as usize;
+            let mut forged = [0u8; 16];
+            for i in idx + 1..16 {
+                forged[i] = intermediate[i] ^ pad;
+            }
+
+            let mut found = None;
+            for guess in candidates.iter().cycle().take(candidates.len() * sweeps) {
+                forged[idx] = guess ^ prev[idx] ^ pad;
+                if !oracle(&forged, block) {
+                    continue;
+                }
+
+                // the plain text might have ended in \x02\x02 (or longer) by chance instead of
+                // \x01. changing the byte before it only keeps the padding valid for \x01.
+                if pad == 1 {
+                    forged[idx - 1] ^= 0xFF;
+                    let confirmed = oracle(&forged, block);
+                    forged[idx - 1] ^= 0xFF;
+                    if !confirmed {
+                        continue;
                     }
                 }
 
-                res
-            })
-            .collect();
-        // println!("final interm: {:?}", interm);
-        // println!("final: {:?}", v);
+                found = Some(forged[idx]);
+                break;
+            }
 
-        let mut res = vec![];
+            intermediate[idx] = found? ^ pad;
+        }
+
+        Some(intermediate)
+    }
+
+    /// Attacks the blocks on a pool of threads, one per core, trying likely plain text bytes
+    /// first.
+    ///
+    /// Queries are shared against `budget` across all threads; once it runs out every thread
+    /// winds down and the reason is returned instead of a partial result.
+    pub fn attack_parallel<F>(
+        &self,
+        oracle: &F,
+        iv: &[u8],
+        cipher: &[u8],
+        budget: &PaddingAttackBudget,
+    ) -> Result<PaddingAttackReport, PaddingAttackError>
+    where
+        F: Fn(&[u8], &[u8]) -> bool + Sync,
+    {
+        if !is_whole_blocks(iv, cipher) {
+            return Err(PaddingAttackError::NotWholeBlocks);
+        }
 
-        for (index, current) in v.iter().enumerate() {
-            let prev = if index == 0 {
-                oracle.iv.to_vec()
+        let deadline = budget.time_limit.map(|limit| Instant::now() + limit);
+        let total_queries = AtomicUsize::new(0);
+        let aborted = AtomicBool::new(false);
+        let next_block = AtomicUsize::new(0);
+
+        let prior = plain_text_prior(&get_english_corpus());
+        let last_block_prior: Vec<u8> = (1..=16)
+            .chain(prior.iter().copied().filter(|b| !(1..=16).contains(b)))
+            .collect();
+
+        let blocks: Vec<&[u8]> = cipher.chunks(16).collect();
+        let attack_block = |index: usize| {
+            let block = blocks[index];
+            let prev = if index == 0 { iv } else { blocks[index - 1] };
+            let candidates = if index == blocks.len() - 1 {
+                &last_block_prior
             } else {
-                let start = 16 * (index - 1);
-                let end = start + 16;
-                c[start..end].to_vec()
+                &prior
             };
 
-            let inner_res = fixed_xor(&prev, current);
-            res.push(inner_res)
+            let queries = Cell::new(0);
+            let counted = |iv: &[u8], c: &[u8]| {
+                if aborted.load(Ordering::SeqCst) {
+                    return false;
+                }
+
+                let total = total_queries.fetch_add(1, Ordering::SeqCst) + 1;
+                let out_of_queries = budget.max_queries.is_some_and(|max| total > max);
+                let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                if out_of_queries || out_of_time {
+                    aborted.store(true, Ordering::SeqCst);
+                    return false;
+                }
+
+                queries.set(queries.get() + 1);
+                oracle(iv, c)
+            };
+
+            self.recover_intermediate_with(&counted, block, prev, candidates, 1)
+                .map(|intermediate| (fixed_xor(prev, &intermediate), queries.get()))
+        };
+
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(blocks.len());
+        let mut results: Vec<Option<(Vec<u8>, usize)>> = vec![None; blocks.len()];
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = vec![];
+                        while !aborted.load(Ordering::SeqCst) {
+                            let index = next_block.fetch_add(1, Ordering::SeqCst);
+                            if index >= blocks.len() {
+                                break;
+                            }
+                            done.push((index, attack_block(index)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                for (index, result) in handle.join().unwrap() {
+                    results[index] = result;
+                }
+            }
+        });
+
+        if aborted.load(Ordering::SeqCst) {
+            let total = total_queries.load(Ordering::SeqCst);
+            return Err(match budget.max_queries {
+                Some(max) if total > max => PaddingAttackError::QueryBudgetExhausted,
+                _ => PaddingAttackError::TimedOut,
+            });
         }
 
-        let s: String = res.concat().iter().map(|c| *c as char).collect();
-        s
+        let mut plain_text = vec![];
+        let mut queries_per_block = vec![];
+        for result in results {
+            let (block, queries) = result.ok_or(PaddingAttackError::OracleNeverAccepted)?;
+            plain_text.extend(block);
+            queries_per_block.push(queries);
+        }
+
+        if !has_padding(&plain_text) {
+            return Err(PaddingAttackError::InvalidPadding);
+        }
+
+        Ok(PaddingAttackReport {
+            plain_text: strip_padding(&plain_text),
+            queries_per_block,
+        })
+    }
+
+    /// `attack` for an oracle that sometimes gets it wrong.
+    ///
+    /// Every accepted guess is asked again `confirmations` times and only kept if most of
+    /// them agree (with none, the first answer stands), bytes that nothing was accepted for get up to `sweeps` passes over the
+    /// candidates, and a result that doesn't come out padded starts over, up to `max_attempts`
+    /// times.
+    pub fn attack_noisy<F>(
+        &self,
+        oracle: F,
+        iv: &[u8],
+        cipher: &[u8],
+        confirmations: usize,
+        sweeps: usize,
+        max_attempts: usize,
+    ) -> Option<Vec<u8>>
+    where
+        F: Fn(&[u8], &[u8]) -> bool,
+    {
+        if !is_whole_blocks(iv, cipher) {
+            return None;
+        }
+
+        let confirming = |iv: &[u8], c: &[u8]| {
+            oracle(iv, c)
+                && (confirmations == 0
+                    || (0..confirmations).filter(|_| oracle(iv, c)).count() * 2 > confirmations)
+        };
+        let candidates: Vec<u8> = (0x00u8..=0xFF).collect();
+
+        'attempts: for _ in 0..max_attempts {
+            let mut res = vec![];
+            let mut prev = iv;
+
+            for block in cipher.chunks(16) {
+                let Some(intermediate) =
+                    self.recover_intermediate_with(&confirming, block, prev, &candidates, sweeps)
+                else {
+                    continue 'attempts;
+                };
+                res.extend(fixed_xor(prev, &intermediate));
+                prev = block;
+            }
+
+            if has_padding(&res) {
+                return Some(strip_padding(&res));
+            }
+        }
+
+        None
+    }
+
+    /// CBC-R: builds an `(iv, cipher)` pair that decrypts to `plain_text` without knowing the key.
+    ///
+    /// Starts from a random last block and works backwards. Once the intermediate state of a
+    /// block is known, the block before it is chosen so that the two XOR to the wanted plain
+    /// text, and the first block's predecessor becomes the IV.
+    pub fn forge_ciphertext<F>(&self, oracle: F, plain_text: &[u8]) -> Option<(Vec<u8>, Vec<u8>)>
+    where
+        F: Fn(&[u8], &[u8]) -> bool,
+    {
+        let padded = pkcs7_padding(plain_text, 16);
+        let mut rng = thread_rng();
+        let last: [u8; 16] = rng.gen();
+
+        let mut blocks = vec![last.to_vec()];
+        for chunk in padded.chunks(16).rev() {
+            let intermediate = self.recover_intermediate(&oracle, blocks.last().unwrap())?;
+            blocks.push(fixed_xor(&intermediate, chunk));
+        }
+
+        blocks.reverse();
+        let iv = blocks.remove(0);
+        Some((iv, blocks.concat()))
+    }
+}
+
+/// What a padding oracle behind some service actually hands back.
+struct OracleResponse {
+    status: u16,
+    body: String,
+    elapsed: Duration,
+}
+
+/// A `PaddingOracle` behind a flaky service, for testing the adapters below.
+///
+/// Valid padding makes it on to a MAC check that fails with a 403 and takes about half a
+/// millisecond longer, bad padding fails straight away with a 500. `error_rate` of the
+/// responses carry the wrong message or a latency spike. No time is actually spent. The key,
+/// the IV and the noise all come from `seed`.
+struct NoisyPaddingOracle {
+    oracle: PaddingOracle,
+    error_rate: f64,
+    rng: Mutex<SmallRng>,
+}
+
+impl NoisyPaddingOracle {
+    pub fn new(error_rate: f64, seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        Self {
+            oracle: PaddingOracle::with_rng(&mut rng),
+            error_rate,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    pub fn encrypt(&self, plain_text: &[u8]) -> (Vec<u8>, [u8; 16]) {
+        self.oracle.encrypt(plain_text)
+    }
+
+    pub fn respond(&self, iv: &[u8], cipher: &[u8]) -> OracleResponse {
+        let valid = self.oracle.is_padding_valid(iv, cipher);
+        let mut rng = self.rng.lock().unwrap();
+
+        let reported = valid ^ rng.gen_bool(self.error_rate);
+        let (status, body) = if reported {
+            (403, "error: message authentication failed")
+        } else {
+            (500, "error: decryption failed (bad padding)")
+        };
+
+        let mut micros = 1000 + rng.gen_range(0..400);
+        if valid {
+            micros += 500;
+        }
+        if rng.gen_bool(self.error_rate) {
+            micros += rng.gen_range(0..2000);
+        }
+
+        OracleResponse {
+            status,
+            body: body.to_string(),
+            elapsed: Duration::from_micros(micros),
+        }
+    }
+}
+
+/// Turns response times into a padding oracle. The median of `samples` measurements above
+/// `threshold` counts as valid padding, which keeps the odd latency spike from deciding it.
+struct TimingOracle<F> {
+    measure: F,
+    threshold: Duration,
+    samples: usize,
+}
+
+impl<F> TimingOracle<F>
+where
+    F: Fn(&[u8], &[u8]) -> Duration,
+{
+    pub fn new(measure: F, threshold: Duration, samples: usize) -> Self {
+        assert!(samples > 0, "a median needs at least one sample");
+        Self {
+            measure,
+            threshold,
+            samples,
+        }
+    }
+
+    pub fn is_padding_valid(&self, iv: &[u8], cipher: &[u8]) -> bool {
+        let mut times: Vec<Duration> = (0..self.samples)
+            .map(|_| (self.measure)(iv, cipher))
+            .collect();
+        times.sort();
+
+        times[times.len() / 2] > self.threshold
+    }
+}
+
+/// Turns error text into a padding oracle: padding is valid when the message matches `valid`.
+struct ErrorMessageOracle<F> {
+    query: F,
+    valid: Regex,
+}
+
+impl<F> ErrorMessageOracle<F>
+where
+    F: Fn(&[u8], &[u8]) -> String,
+{
+    pub fn is_padding_valid(&self, iv: &[u8], cipher: &[u8]) -> bool {
+        self.valid.is_match(&(self.query)(iv, cipher))
     }
 }
 
 pub fn use_ctr_mode(key: &[u8], nonce: &[u8], plain_text: &[u8]) -> Vec<u8> {
-    let mut keystream = vec![vec![]];
+    let keystream = ctr_keystream(key, nonce, 0, plain_text.len());
+    fixed_xor(&keystream, plain_text)
+}
 
-    let res: Vec<Vec<_>> = plain_text
-        .chunks(16)
-        .zip(0..(plain_text.len() as f32 / 16f32).ceil() as usize)
-        .map(|(chunk, counter)| {
+/// `len` bytes of the CTR keystream starting at byte `offset`, without generating what comes before it.
+pub fn ctr_keystream(key: &[u8], nonce: &[u8], offset: usize, len: usize) -> Vec<u8> {
+    if len == 0 {
+        return vec![];
+    }
+
+    let first_block = offset / 16;
+    let last_block = (offset + len - 1) / 16;
+
+    let blocks: Vec<Vec<u8>> = (first_block..=last_block)
+        .map(|counter| {
             let counter: [u8; 8] = u64::to_le_bytes(counter as u64);
             let comb = [nonce, &counter].concat();
-            let enc_res = encrypt_aes_ecb(key, &comb);
-            keystream.push(enc_res.clone());
-
-            fixed_xor(&enc_res, chunk)
+            encrypt_aes_ecb(key, &comb)
         })
         .collect();
 
-    res.concat()
+    let skip = offset % 16;
+    blocks.concat()[skip..skip + len].to_vec()
 }
 
-pub fn break_fixed_nonce_ctr(plain_texts: Vec<Vec<u8>>) -> String {
-    // encrypt all lines
-    let nonce = [0; 8];
-    let mut encrypted_res = vec![];
-    let mut rng = thread_rng();
-    let key: [u8; 16] = rng.gen();
+/// A recovered keystream byte. `confidence` is the probability of `value` against every other
+/// byte value, treating the English model's likelihoods as a posterior.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeystreamByte {
+    pub value: u8,
+    pub confidence: f64,
+}
 
-    for text in plain_texts {
-        let res = use_ctr_mode(&key, &nonce, &text);
-        encrypted_res.push(res)
-    }
+/// Breaks CTR cipher texts that were all encrypted under the same key and nonce.
+///
+/// Every column (the bytes at the same offset in each cipher text) was XORed with the same
+/// keystream byte, so each is a single-byte XOR on its own. Columns with fewer than
+/// `min_samples` bytes are too short to call and come back as `None`.
+///
+/// Guesses that differ by 0x20 turn the same letters into the other case, and a model of single
+/// characters would pick lowercase every time. So letters are scored ignoring case and spaces,
+/// digits and punctuation decide. A column of nothing but letters can't be told apart that way
+/// and reports about half the confidence; the first column takes the capitals that start lines,
+/// any other the more common case.
+pub fn break_fixed_nonce_ctr(
+    ciphers: &[Vec<u8>],
+    min_samples: usize,
+) -> Vec<Option<KeystreamByte>> {
     let corpus = get_english_corpus();
-    let uppercase_corpus = get_uppercase_corpus();
+    let folded_corpus = get_case_insensitive_corpus(&corpus);
+    let longest = ciphers.iter().map(|c| c.len()).max().unwrap_or(0);
 
-    // get keysize (max length of lines)
-    let max_len = encrypted_res.iter().min_by_key(|x| x.len()).unwrap().len();
-    let truncated: Vec<Vec<u8>> = encrypted_res
-        .iter()
-        .map(|v| v[..max_len].to_vec())
-        .collect();
+    (0..longest)
+        .map(|column| {
+            let samples: Vec<u8> = ciphers
+                .iter()
+                .filter_map(|c| c.get(column).copied())
+                .collect();
+            if samples.len() < min_samples.max(1) {
+                return None;
+            }
 
-    // transpose blocks
-    let blocks = transpose(&truncated.concat(), max_len);
+            let scores: Vec<(f64, f64)> = (0x00u8..=0xFF)
+                .map(|k| {
+                    let guess = fixed_xor(&samples, &vec![k; samples.len()]);
+                    let case = match column {
+                        0 => guess.iter().filter(|b| b.is_ascii_uppercase()).count() as f64,
+                        _ => get_log_likelihood_of_english(&guess, &corpus),
+                    };
+                    (get_log_likelihood_of_english(&guess, &folded_corpus), case)
+                })
+                .collect();
+            let (value, (best, _)) = scores
+                .iter()
+                .enumerate()
+                .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+                .unwrap();
+            let confidence = 1.0 / scores.iter().map(|(s, _)| (s - best).exp()).sum::<f64>();
 
-    let mut keystream = vec![];
+            Some(KeystreamByte {
+                value: value as u8,
+                confidence,
+            })
+        })
+        .collect()
+}
 
-    // get keystream using single byte xor
-    blocks.iter().enumerate().for_each(|(index, block)| {
-        let xor_res = if index == 0 {
-            single_byte_xor(block, &uppercase_corpus)
-        } else {
-            single_byte_xor(block, &corpus)
-        };
-        // println!("{}: {}", &xor_res.0, &xor_res.1);
-        keystream.push(xor_res.0);
-    });
-
-    let mut results = String::new();
-    // xor keystream with cipher to get plain text
-    for cipher in encrypted_res {
-        let res = fixed_xor(&keystream, &cipher);
-        results = format!("{}\n{}", results, String::from_utf8(res).unwrap())
-    }
-
-    results
-}
-
-struct MersenneTwisterRNG {
-    w: u8,
-    n: u16,
-    m: u16,
-    r: u8,
-    a: u32,
-    u: u8,
-    d: u32,
-    s: u8,
-    b: u32,
-    t: u8,
-    c: u32,
-    l: u8,
-    f: u32,
-    mt: [u32; 624], // 624 is the value of n
-    lower_mask: u32,
-    upper_mask: u32,
-    index: u16,
+/// A spot where a crib placed in cipher text `crib_in` makes the others read like English.
+/// `revealed` holds what each overlapping cipher text says at that offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    pub crib_in: usize,
+    pub offset: usize,
+    pub revealed: Vec<(usize, Vec<u8>)>,
+    pub score: f64,
 }
 
-impl MersenneTwisterRNG {
-    pub fn new(seed: Option<u32>) -> Self {
-        let r = 31;
-        let lower_mask = (1 << r) - 1;
-        const N: u16 = 624;
+/// Crib-dragging over cipher texts that share a keystream (fixed-nonce CTR, many-time pads).
+///
+/// XORing two such cipher texts cancels the keystream and leaves the XOR of their plain texts,
+/// so a word guessed in one reveals the bytes at the same offset in the other. Pinned
+/// keystream bytes apply to every cipher text at once.
+pub struct CribDragger {
+    ciphers: Vec<Vec<u8>>,
+    keystream: Vec<Option<u8>>,
+}
+
+impl CribDragger {
+    pub fn new(ciphers: Vec<Vec<u8>>) -> Self {
+        let longest = ciphers.iter().map(|c| c.len()).max().unwrap_or(0);
+
+        Self {
+            ciphers,
+            keystream: vec![None; longest],
+        }
+    }
+
+    pub fn ciphers(&self) -> &[Vec<u8>] {
+        &self.ciphers
+    }
+
+    /// Takes the statistical guesses from `break_fixed_nonce_ctr` that are at least `min_confidence` sure.
+    pub fn seed(&mut self, guesses: &[Option<KeystreamByte>], min_confidence: f64) {
+        for (k, guess) in self.keystream.iter_mut().zip(guesses) {
+            *k = guess
+                .filter(|g| g.confidence >= min_confidence)
+                .map(|g| g.value);
+        }
+    }
+
+    /// Every place `crib` fits, most English-looking first.
+    ///
+    /// For each cipher text and offset, the crib is XORed against that cipher text's XOR with
+    /// every other one that's long enough. The score is the average per-character likelihood of
+    /// everything revealed, so a wrong guess has to make every overlapping line look English.
+    /// An empty crib reveals nothing and fits nowhere.
+    pub fn drag(&self, crib: &[u8]) -> Vec<CribMatch> {
+        if crib.is_empty() {
+            return vec![];
+        }
+
+        let corpus = get_english_corpus();
+        let mut matches = vec![];
+
+        for (i, first) in self.ciphers.iter().enumerate() {
+            if first.len() < crib.len() {
+                continue;
+            }
+
+            for offset in 0..=first.len() - crib.len() {
+                let end = offset + crib.len();
+                let revealed: Vec<(usize, Vec<u8>)> = self
+                    .ciphers
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, second)| *j != i && second.len() >= end)
+                    .map(|(j, second)| {
+                        let xored = fixed_xor(&first[offset..end], &second[offset..end]);
+                        (j, fixed_xor(&xored, crib))
+                    })
+                    .collect();
+                if revealed.is_empty() {
+                    continue;
+                }
+
+                let total: f64 = revealed
+                    .iter()
+                    .map(|(_, r)| get_log_likelihood_of_english(r, &corpus))
+                    .sum();
+                matches.push(CribMatch {
+                    crib_in: i,
+                    offset,
+                    score: total / (revealed.len() * crib.len()) as f64,
+                    revealed,
+                });
+            }
+        }
+
+        matches.sort_by(|m1, m2| m2.score.partial_cmp(&m1.score).unwrap());
+        matches
+    }
+
+    /// Fixes the keystream so that cipher text `index` reads `text` starting at `offset`.
+    pub fn pin(&mut self, index: usize, offset: usize, text: &[u8]) {
+        let cipher = &self.ciphers[index];
+        let end = (offset + text.len()).min(cipher.len());
+        if offset >= end {
+            return;
+        }
+
+        for (k, b) in fixed_xor(&cipher[offset..end], text)
+            .into_iter()
+            .enumerate()
+        {
+            self.keystream[offset + k] = Some(b);
+        }
+    }
+
+    pub fn unpin(&mut self, offset: usize, len: usize) {
+        let end = (offset + len).min(self.keystream.len());
+        for k in self.keystream.iter_mut().take(end).skip(offset) {
+            *k = None;
+        }
+    }
+
+    /// Every cipher text under the current keystream, as raw bytes with `None` where nothing is pinned.
+    pub fn decrypt(&self) -> Vec<Vec<Option<u8>>> {
+        self.ciphers
+            .iter()
+            .map(|cipher| {
+                cipher
+                    .iter()
+                    .zip(&self.keystream)
+                    .map(|(c, k)| k.map(|k| c ^ k))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `decrypt` for a terminal: `_` for unknown bytes and `.` for anything unprintable.
+    pub fn render(&self) -> Vec<String> {
+        self.decrypt()
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|b| match b {
+                        None => '_',
+                        Some(b) if b == b' ' || b.is_ascii_graphic() => b as char,
+                        Some(_) => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// What a Mersenne Twister can be built on. Only `u32` and `u64` are needed in practice.
+pub trait MtWord:
+    Copy
+    + PartialEq
+    + std::fmt::Debug
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    const BITS: u8;
+    const ZERO: Self;
+    const ONE: Self;
+    /// The two multipliers `init_by_array` uses in the reference generator of this width.
+    const ARRAY_MULTIPLIERS: [Self; 2];
+
+    fn shl(self, n: u8) -> Self;
+    fn shr(self, n: u8) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_mt_word {
+    ($t:ty, $multipliers:expr) => {
+        impl MtWord for $t {
+            const BITS: u8 = <$t>::BITS as u8;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const ARRAY_MULTIPLIERS: [Self; 2] = $multipliers;
+
+            fn shl(self, n: u8) -> Self {
+                self.checked_shl(n as u32).unwrap_or(0)
+            }
+
+            fn shr(self, n: u8) -> Self {
+                self.checked_shr(n as u32).unwrap_or(0)
+            }
+
+            fn wrapping_add(self, other: Self) -> Self {
+                <$t>::wrapping_add(self, other)
+            }
+
+            fn wrapping_sub(self, other: Self) -> Self {
+                <$t>::wrapping_sub(self, other)
+            }
+
+            fn wrapping_mul(self, other: Self) -> Self {
+                <$t>::wrapping_mul(self, other)
+            }
+
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+        }
+    };
+}
+
+impl_mt_word!(u32, [1664525, 1566083941]);
+impl_mt_word!(u64, [3935559000370003845, 2862933555777941757]);
+
+/// The constants that make a Mersenne Twister, named as in the reference description.
+/// `w` can be less than the width of the word type, in which case everything is kept to the
+/// low `w` bits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MtParams<W> {
+    pub w: u8,
+    pub n: usize,
+    pub m: usize,
+    pub r: u8,
+    pub a: W,
+    pub u: u8,
+    pub d: W,
+    pub s: u8,
+    pub b: W,
+    pub t: u8,
+    pub c: W,
+    pub l: u8,
+    pub f: W,
+}
+
+pub const MT19937: MtParams<u32> = MtParams {
+    w: 32,
+    n: 624,
+    m: 397,
+    r: 31,
+    a: 0x9908B0DF,
+    u: 11,
+    d: 0xFFFFFFFF,
+    s: 7,
+    b: 0x9D2C5680,
+    t: 15,
+    c: 0xEFC60000,
+    l: 18,
+    f: 1812433253,
+};
+
+pub const MT19937_64: MtParams<u64> = MtParams {
+    w: 64,
+    n: 312,
+    m: 156,
+    r: 31,
+    a: 0xB5026F5AA96619E9,
+    u: 29,
+    d: 0x5555555555555555,
+    s: 17,
+    b: 0x71D67FFFEDA60000,
+    t: 37,
+    c: 0xFFF7EEE000000000,
+    l: 43,
+    f: 6364136223846793005,
+};
+
+/// Seed the reference implementations fall back to.
+const MT_DEFAULT_SEED: usize = 5489;
+
+impl<W: MtWord> MtParams<W> {
+    /// Panics on parameters the generator can't run with: a word wider than `W`, a split `r`
+    /// outside the word, `m` not inside the state, or a tempering shift of nothing.
+    fn validate(&self) {
+        assert!(
+            (2..=W::BITS).contains(&self.w),
+            "w = {} doesn't fit a {}-bit word",
+            self.w,
+            W::BITS
+        );
+        assert!(self.r < self.w, "r = {} isn't below w = {}", self.r, self.w);
+        assert!(
+            0 < self.m && self.m < self.n,
+            "m = {} isn't between 0 and n = {}",
+            self.m,
+            self.n
+        );
+        for shift in [self.u, self.s, self.t, self.l] {
+            assert!(
+                0 < shift && shift < self.w,
+                "tempering shift {} isn't between 0 and w = {}",
+                shift,
+                self.w
+            );
+        }
+    }
+
+    /// The low `w` bits.
+    fn word_mask(&self) -> W {
+        (!W::ZERO).shr(W::BITS - self.w)
+    }
+
+    /// The part of the twist that scrambles a word: shift it down one, and XOR in `a` if the
+    /// bit that fell off was set.
+    fn multiply_by_a(&self, x: W) -> W {
+        match x & W::ONE == W::ONE {
+            true => x.shr(1) ^ self.a,
+            false => x.shr(1),
+        }
+    }
+
+    /// Undoes `multiply_by_a`. A word shifted down never has its top bit set, so when `a`
+    /// does, the top bit says whether `a` went in. When it doesn't, two words multiply to the
+    /// same thing and there's no telling which it was, so `None`.
+    fn divide_by_a(&self, y: W) -> Option<W> {
+        if !has_bit(self.a, self.w - 1) {
+            return None;
+        }
+        match has_bit(y, self.w - 1) {
+            true => Some((y ^ self.a).shl(1) | W::ONE),
+            false => Some(y.shl(1)),
+        }
+    }
+
+    pub fn temper(&self, y: W) -> W {
+        let y = y ^ (y.shr(self.u) & self.d);
+        let y = y ^ (y.shl(self.s) & self.b);
+        let y = y ^ (y.shl(self.t) & self.c);
+        y ^ y.shr(self.l)
+    }
+
+    /// Turns an output back into the state word it was tempered from.
+    pub fn untemper(&self, y: W) -> W {
+        let y = undo_right_shift_xor(y, self.l, !W::ZERO);
+        let y = undo_left_shift_xor(y, self.t, self.c);
+        let y = undo_left_shift_xor(y, self.s, self.b);
+        undo_right_shift_xor(y, self.u, self.d)
+    }
+}
+
+/// Undoes `y ^= (y >> shift) & mask`. The top `shift` bits came through untouched, and each
+/// pass recovers the next `shift` below them.
+fn undo_right_shift_xor<W: MtWord>(y: W, shift: u8, mask: W) -> W {
+    let mut x = y;
+    for _ in 0..W::BITS / shift {
+        x = y ^ (x.shr(shift) & mask);
+    }
+    x
+}
+
+/// Undoes `y ^= (y << shift) & mask`, working up from the bottom bits.
+fn undo_left_shift_xor<W: MtWord>(y: W, shift: u8, mask: W) -> W {
+    let mut x = y;
+    for _ in 0..W::BITS / shift {
+        x = y ^ (x.shl(shift) & mask);
+    }
+    x
+}
+
+/// Turns an output of MT19937 back into the state word it was tempered from.
+pub fn untemper(y: u32) -> u32 {
+    MT19937.untemper(y)
+}
+
+/// Turns an output of MT19937-64 back into the state word it was tempered from.
+pub fn untemper_64(y: u64) -> u64 {
+    MT19937_64.untemper(y)
+}
+
+pub struct MersenneTwister<W> {
+    params: MtParams<W>,
+    mt: Vec<W>,
+    lower_mask: W,
+    upper_mask: W,
+    index: usize,
+}
+
+pub type MersenneTwisterRNG = MersenneTwister<u32>;
+pub type MersenneTwister64 = MersenneTwister<u64>;
+
+impl<W: MtWord> MersenneTwister<W> {
+    /// Panics if `params` don't describe a generator that can run, see `MtParams::validate`.
+    pub fn with_params(params: MtParams<W>, seed: W) -> Self {
+        params.validate();
+        let lower_mask = (!W::ZERO).shr(W::BITS - params.r);
 
         let mut s = Self {
-            w: 32,
-            n: N,
-            m: 397,
-            r,
-            a: 0x9908B0DF,
-            u: 11,
-            d: 0xFFFFFFFF,
-            s: 7,
-            b: 0x9D2C5680,
-            t: 15,
-            c: 0xEFC60000,
-            l: 18,
-            f: 1812433253,
-            mt: [0; N as usize],
+            params,
+            mt: vec![W::ZERO; params.n],
             lower_mask,
-            upper_mask: !lower_mask,
-            index: N,
+            upper_mask: !lower_mask & params.word_mask(),
+            index: params.n,
         };
+        s.seed_mt(seed);
+        s
+    }
 
-        if let Some(value) = seed {
-            s.seed_mt(value);
-        } else {
-            s.seed_mt(5489);
-        }
+    /// Seeded from a whole array of words, the way most languages seed it.
+    pub fn with_key(params: MtParams<W>, key: &[W]) -> Self {
+        let mut s = Self::with_params(params, W::ZERO);
+        s.init_by_array(key);
+        s
+    }
 
+    /// A generator that carries on from `mt`, with `index` outputs of it already used. Panics
+    /// unless `mt` is the `n` words of a state and `index` is within it.
+    pub fn from_state_with(params: MtParams<W>, mt: &[W], index: usize) -> Self {
+        assert_eq!(params.n, mt.len(), "the state is n words");
+        assert!(index <= params.n, "index {} is past the state", index);
+        let mut s = Self::with_params(params, W::ZERO);
+        s.mt.copy_from_slice(mt);
+        s.index = index;
         s
     }
 
-    fn seed_mt(&mut self, seed: u32) {
-        let index = self.n as usize;
-        self.mt[0] = seed;
-        for i in 1usize..index {
-            self.mt[i] = self
-                .f
-                .wrapping_mul(self.mt[i - 1] ^ (self.mt[i - 1] >> (self.w - 2)))
-                .wrapping_add(i as u32);
+    /// Rebuilds the state from the first `n` of `outputs`, then checks it against the rest.
+    /// Any `n` in a row will do, wherever they fall between twists: every state word only
+    /// depends on the three that came `n`, `n - 1` and `n - m` outputs before it. The clone
+    /// picks up where `outputs` leaves off, and `None` means there weren't enough outputs or
+    /// they didn't come from one untouched generator.
+    pub fn clone_from_outputs_with(params: MtParams<W>, outputs: &[W]) -> Option<Self> {
+        if outputs.len() < params.n {
+            return None;
         }
+
+        let mt: Vec<W> = outputs[..params.n]
+            .iter()
+            .map(|&output| params.untemper(output))
+            .collect();
+
+        let mut clone = Self::from_state_with(params, &mt, params.n);
+        for &output in &outputs[params.n..] {
+            if clone.extract_number() != output {
+                return None;
+            }
+        }
+        Some(clone)
     }
 
-    fn extract_number(&mut self) -> u32 {
-        if self.index >= self.n {
-            self.twist()
+    /// Like `clone_from_outputs_with`, but with a `None` in `outputs` for each call we didn't
+    /// get to see. The gaps are filled in from the recurrence `twist` follows on the untempered
+    /// words, `y[k + n] = y[k + m] ^ A(upper(y[k]) | lower(y[k + 1]))`, which can be run
+    /// forwards for `y[k + n]`, rearranged for `y[k + m]`, or, when `a` has its top bit set,
+    /// undone through `A` for the top of `y[k]` and the bottom of `y[k + 1]`. Once `n` in a row
+    /// are known the clone is built from those and checked against the rest. `None` if the gaps
+    /// can't all be filled that way, which `MtStateSolver` may still manage.
+    pub fn clone_from_gapped_outputs_with(
+        params: MtParams<W>,
+        outputs: &[Option<W>],
+    ) -> Option<Self> {
+        let p = params;
+        let full = p.word_mask();
+        let lower = (!W::ZERO).shr(W::BITS - p.r);
+        let upper = full & !lower;
+
+        let mut words: Vec<W> = outputs
+            .iter()
+            .map(|output| output.map_or(W::ZERO, |output| p.untemper(output)))
+            .collect();
+        let mut known: Vec<W> = outputs
+            .iter()
+            .map(|output| if output.is_some() { full } else { W::ZERO })
+            .collect();
+
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for k in 0..outputs.len().saturating_sub(p.n) {
+                let (next, ahead) = (k + p.n, k + p.m);
+                let mixed_known = known[k] & upper == upper && known[k + 1] & lower == lower;
+                let mixed = p.multiply_by_a((words[k] & upper) | (words[k + 1] & lower));
+
+                match (known[next] == full, known[ahead] == full) {
+                    (false, true) if mixed_known => {
+                        words[next] = words[ahead] ^ mixed;
+                        known[next] = full;
+                    }
+                    (true, false) if mixed_known => {
+                        words[ahead] = words[next] ^ mixed;
+                        known[ahead] = full;
+                    }
+                    (true, true) if !mixed_known => {
+                        let Some(x) = p.divide_by_a(words[next] ^ words[ahead]) else {
+                            continue;
+                        };
+                        words[k] = (words[k] & lower) | (x & upper);
+                        known[k] = known[k] | upper;
+                        words[k + 1] = (words[k + 1] & upper) | (x & lower);
+                        known[k + 1] = known[k + 1] | lower;
+                    }
+                    _ => continue,
+                }
+                progress = true;
+            }
         }
 
-        let mut y = self.mt[self.index as usize];
-        y = y ^ ((y >> self.u) & self.d);
-        y = y ^ ((y << self.s) & self.b);
-        y = y ^ ((y << self.t) & self.c);
-        y = y ^ (y >> self.l);
+        let start = (0..(outputs.len() + 1).saturating_sub(p.n))
+            .find(|&start| known[start..start + p.n].iter().all(|&k| k == full))?;
+        let mut clone = Self::from_state_with(p, &words[start..start + p.n], p.n);
+        for output in &outputs[start + p.n..] {
+            let next = clone.extract_number();
+            if output.is_some_and(|output| output != next) {
+                return None;
+            }
+        }
+        Some(clone)
+    }
+
+    fn seed_mt(&mut self, seed: W) {
+        let p = self.params;
+        self.mt[0] = seed & p.word_mask();
+        for i in 1..p.n {
+            let previous = self.mt[i - 1];
+            self.mt[i] =
+                p.f.wrapping_mul(previous ^ previous.shr(p.w - 2))
+                    .wrapping_add(W::from_usize(i))
+                    & p.word_mask();
+        }
+        self.index = p.n;
+    }
+
+    /// `init_by_array` from the reference implementation: start from the seed 19650218, then
+    /// mix `key` through the whole state twice over.
+    pub fn init_by_array(&mut self, key: &[W]) {
+        let p = self.params;
+        let [first, second] = W::ARRAY_MULTIPLIERS;
+        let key = if key.is_empty() { &[W::ZERO][..] } else { key };
+        let mix = |mt: &[W], i: usize, multiplier: W| {
+            mt[i] ^ (mt[i - 1] ^ mt[i - 1].shr(p.w - 2)).wrapping_mul(multiplier)
+        };
 
+        self.seed_mt(W::from_usize(19650218));
+        let (mut i, mut j) = (1, 0);
+        for _ in 0..p.n.max(key.len()) {
+            self.mt[i] = mix(&self.mt, i, first)
+                .wrapping_add(key[j])
+                .wrapping_add(W::from_usize(j))
+                & p.word_mask();
+            i += 1;
+            j += 1;
+            if i >= p.n {
+                self.mt[0] = self.mt[p.n - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..p.n - 1 {
+            self.mt[i] = mix(&self.mt, i, second).wrapping_sub(W::from_usize(i)) & p.word_mask();
+            i += 1;
+            if i >= p.n {
+                self.mt[0] = self.mt[p.n - 1];
+                i = 1;
+            }
+        }
+
+        // the top bit only, so the state can't be all zeros
+        self.mt[0] = W::ONE.shl(p.w - 1);
+        self.index = p.n;
+    }
+
+    fn extract_number(&mut self) -> W {
+        if self.index >= self.params.n {
+            self.twist()
+        }
+
+        let y = self.params.temper(self.mt[self.index]);
         self.index += 1;
         y
     }
 
     fn twist(&mut self) {
-        for i in 0..self.n {
-            let x = (self.mt[i as usize] & self.upper_mask)
-                + (self.mt[((i + 1) % self.n) as usize] & self.lower_mask);
-            let mut xa = x >> 1;
-            if x % 2 != 0 {
-                xa ^= self.a;
+        let (n, m) = (self.params.n, self.params.m);
+        for i in 0..n {
+            let x = (self.mt[i] & self.upper_mask) | (self.mt[(i + 1) % n] & self.lower_mask);
+            self.mt[i] = self.mt[(i + m) % n] ^ self.params.multiply_by_a(x)
+        }
+
+        self.index = 0
+    }
+}
+
+impl MersenneTwisterRNG {
+    pub fn new(seed: Option<u32>) -> Self {
+        Self::with_params(MT19937, seed.unwrap_or(MT_DEFAULT_SEED as u32))
+    }
+
+    pub fn from_state(mt: [u32; 624], index: usize) -> Self {
+        Self::from_state_with(MT19937, &mt, index)
+    }
+
+    pub fn clone_from_outputs(outputs: &[u32]) -> Option<Self> {
+        Self::clone_from_outputs_with(MT19937, outputs)
+    }
+
+    pub fn clone_from_gapped_outputs(outputs: &[Option<u32>]) -> Option<Self> {
+        Self::clone_from_gapped_outputs_with(MT19937, outputs)
+    }
+}
+
+impl MersenneTwisterRNG {
+    /// Clones a generator from bytes it gave out through `fill_bytes`, which hands out each
+    /// number as four little-endian bytes. Needs at least 624 numbers' worth.
+    pub fn clone_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let outputs: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Self::clone_from_outputs(&outputs)
+    }
+}
+
+impl RngCore for MersenneTwisterRNG {
+    fn next_u32(&mut self) -> u32 {
+        self.extract_number()
+    }
+
+    /// Two numbers, the first in the low half.
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        (self.next_u32() as u64) << 32 | low
+    }
+
+    /// Each number as four little-endian bytes, the last one cut short if needed.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for MersenneTwisterRNG {
+    type Seed = [u8; 4];
+
+    /// The single-word seeding, with the seed read little-endian.
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(Some(u32::from_le_bytes(seed)))
+    }
+
+    /// Seeds through `init_by_array` the way Python does, so no bits of `state` are lost.
+    fn seed_from_u64(state: u64) -> Self {
+        Self::from_python_seed(state as u128)
+    }
+}
+
+/// `seed` as 32-bit words, least significant first, the way Python and Ruby turn an integer
+/// seed into a key. Zero still gives one word.
+fn seed_words(seed: u128) -> Vec<u32> {
+    let mut words = vec![];
+    let mut rest = seed;
+    while rest > 0 || words.is_empty() {
+        words.push(rest as u32);
+        rest >>= 32;
+    }
+    words
+}
+
+/// Ways various languages get numbers out of MT19937, so their outputs can be reproduced
+/// and predicted.
+impl MersenneTwisterRNG {
+    /// Python's `random.seed(n)` for a non-negative integer `n`.
+    pub fn from_python_seed(seed: u128) -> Self {
+        Self::with_key(MT19937, &seed_words(seed))
+    }
+
+    /// Python's `random.random()`: 53 random bits out of two numbers.
+    pub fn python_random(&mut self) -> f64 {
+        let a = (self.extract_number() >> 5) as f64;
+        let b = (self.extract_number() >> 6) as f64;
+        (a * 67108864.0 + b) * (1.0 / 9007199254740992.0)
+    }
+
+    /// Python's `random.getrandbits(k)`. Fills 32 bits at a time from the bottom, and the
+    /// last number only gives its top bits. Panics if `k` is over 128.
+    pub fn python_getrandbits(&mut self, k: u32) -> u128 {
+        assert!(k <= 128, "only up to 128 bits fit");
+
+        let mut res = 0;
+        let mut remaining = k;
+        let mut shift = 0;
+        while remaining > 0 {
+            let mut r = self.extract_number();
+            if remaining < 32 {
+                r >>= 32 - remaining;
+            }
+            res |= (r as u128) << shift;
+            shift += 32;
+            remaining = remaining.saturating_sub(32);
+        }
+        res
+    }
+
+    /// PHP's `mt_srand(seed)`, which since 7.1 is the plain single-word seed.
+    pub fn from_php_seed(seed: u32) -> Self {
+        Self::new(Some(seed))
+    }
+
+    /// PHP's `mt_rand()`, which drops the lowest bit.
+    pub fn php_mt_rand(&mut self) -> u32 {
+        self.extract_number() >> 1
+    }
+
+    /// PHP's `mt_rand(min, max)`. Uses whole numbers, masking for powers of two and throwing
+    /// away the top of the range that would bias the remainder otherwise. Ranges wider than
+    /// 32 bits take two numbers at a time, high word first, like 64-bit PHP does.
+    pub fn php_mt_rand_range(&mut self, min: i64, max: i64) -> i64 {
+        let umax = (max as u64).wrapping_sub(min as u64);
+        let result = match u32::try_from(umax) {
+            Ok(umax) => self.php_rand_range32(umax) as u64,
+            Err(_) => self.php_rand_range64(umax),
+        };
+        (min as u64).wrapping_add(result) as i64
+    }
+
+    fn php_rand_range32(&mut self, umax: u32) -> u32 {
+        let mut result = self.extract_number();
+        if umax == u32::MAX {
+            return result;
+        }
+
+        let range = umax + 1;
+        if range & (range - 1) == 0 {
+            return result & (range - 1);
+        }
+
+        let limit = u32::MAX - (u32::MAX % range) - 1;
+        while result > limit {
+            result = self.extract_number();
+        }
+        result % range
+    }
+
+    fn php_rand_range64(&mut self, umax: u64) -> u64 {
+        let mut result = self.php_next_u64();
+        if umax == u64::MAX {
+            return result;
+        }
+
+        let range = umax + 1;
+        if range & (range - 1) != 0 {
+            let limit = u64::MAX - (u64::MAX % range) - 1;
+            while result > limit {
+                result = self.php_next_u64();
             }
+        }
+        result % range
+    }
+
+    fn php_next_u64(&mut self) -> u64 {
+        let high = self.extract_number() as u64;
+        high << 32 | self.extract_number() as u64
+    }
 
-            self.mt[i as usize] = self.mt[((i + self.m) % self.n) as usize] ^ xa
+    /// Ruby's `Random.new(seed)`. A seed that fits in one word uses the single-word seeding,
+    /// anything bigger goes through `init_by_array`.
+    pub fn from_ruby_seed(seed: u128) -> Self {
+        match seed_words(seed)[..] {
+            [word] => Self::new(Some(word)),
+            ref words => Self::with_key(MT19937, words),
         }
+    }
 
-        self.index = 0
+    /// Ruby's `rand`, which builds its float the same way as Python.
+    pub fn ruby_rand(&mut self) -> f64 {
+        self.python_random()
+    }
+
+    /// Ruby's `rand(n)`: masks numbers down to the bits `n - 1` needs, top word first, and
+    /// starts over whenever that goes past `n - 1`.
+    pub fn ruby_rand_int(&mut self, n: u64) -> u64 {
+        let limit = n.saturating_sub(1);
+        if limit == 0 {
+            return 0;
+        }
+        let mask = u64::MAX >> limit.leading_zeros();
+
+        'retry: loop {
+            let mut val = 0;
+            for i in (0..2).rev() {
+                if (mask >> (i * 32)) & 0xFFFFFFFF != 0 {
+                    val |= (self.extract_number() as u64) << (i * 32);
+                    val &= mask;
+                    if limit < val {
+                        continue 'retry;
+                    }
+                }
+            }
+            return val;
+        }
     }
 }
 
-fn mersenne_twister_with_timestamp_seed() -> (u32, u32) {
-    // wait a random number of seconds between 50 and 1000
+impl MersenneTwister64 {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self::with_params(MT19937_64, seed.unwrap_or(MT_DEFAULT_SEED as u64))
+    }
+
+    pub fn from_state(mt: [u64; 312], index: usize) -> Self {
+        Self::from_state_with(MT19937_64, &mt, index)
+    }
+
+    pub fn clone_from_outputs(outputs: &[u64]) -> Option<Self> {
+        Self::clone_from_outputs_with(MT19937_64, outputs)
+    }
+}
+
+/// Whether bit `bit` of `x` is set.
+fn has_bit<W: MtWord>(x: W, bit: u8) -> bool {
+    x.shr(bit) & W::ONE == W::ONE
+}
+
+/// Recovers the state of a Mersenne Twister from some of the bits of its outputs, like the
+/// top few bits of each number, the remainder of `rand() % 8` or a float. Twisting and
+/// tempering are both linear over GF(2), so every output bit is the XOR of some of the bits
+/// of the starting state. Each known bit is an equation over those, and once there are enough
+/// of them Gaussian elimination gives back the state.
+///
+/// Outputs can be numbered from any point in the stream, and any of them skipped. It
+/// takes at least `n * w - r` known bits, and a good few more when they're low bits, which
+/// say less about the state.
+pub struct MtStateSolver<W> {
+    params: MtParams<W>,
+    /// Output index, which of its bits are known and what they are.
+    observations: Vec<(usize, W, W)>,
+}
+
+impl<W: MtWord> MtStateSolver<W> {
+    pub fn new(params: MtParams<W>) -> Self {
+        Self {
+            params,
+            observations: vec![],
+        }
+    }
+
+    /// The bits of output `index` that are set in `mask` are the ones in `bits`.
+    pub fn observe(&mut self, index: usize, mask: W, bits: W) {
+        let mask = mask & self.params.word_mask();
+        self.observations.push((index, mask, bits & mask));
+    }
+
+    /// Output `index` shifted down to its top `count` bits was `bits`. Panics if `count` is
+    /// more than the `w` bits an output has.
+    pub fn observe_high_bits(&mut self, index: usize, count: u8, bits: W) {
+        assert!(
+            count <= self.params.w,
+            "outputs only have {} bits",
+            self.params.w
+        );
+        let shift = self.params.w - count;
+        self.observe(index, (!W::ZERO).shl(shift), bits.shl(shift));
+    }
+
+    /// The bottom `count` bits of output `index` were `bits`. Panics like `observe_high_bits`.
+    pub fn observe_low_bits(&mut self, index: usize, count: u8, bits: W) {
+        assert!(
+            count <= self.params.w,
+            "outputs only have {} bits",
+            self.params.w
+        );
+        let mask = !(!W::ZERO).shl(count);
+        self.observe(index, mask, bits);
+    }
+
+    /// Solves for the state and hands back a generator that picks up after the last observed
+    /// output. `None` if the observations don't pin down one state, or contradict each other.
+    pub fn solve(&self) -> Option<MersenneTwister<W>> {
+        let p = self.params;
+        let w = p.w as usize;
+        let last = self.observations.iter().map(|&(i, _, _)| i).max()?;
+
+        // one variable per bit of the state the first `n` outputs are tempered from
+        let variables = p.n * w;
+        let row_len = variables.div_ceil(64);
+        let mut state: Vec<Vec<Gf2Row>> = (0..p.n)
+            .map(|word| {
+                (0..w)
+                    .map(|bit| {
+                        let mut row = vec![0; row_len];
+                        flip_row_bit(&mut row, word * w + bit);
+                        row
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // which bits of the state word each output bit is the XOR of
+        let tempering: Vec<Vec<usize>> = (0..p.w)
+            .map(|out| {
+                (0..p.w)
+                    .filter(|&bit| has_bit(p.temper(W::ONE.shl(bit)), out))
+                    .map(|bit| bit as usize)
+                    .collect()
+            })
+            .collect();
+
+        let mut by_index: Vec<Vec<(W, W)>> = vec![vec![]; last + 1];
+        for &(index, mask, bits) in &self.observations {
+            by_index[index].push((mask, bits));
+        }
+
+        // Equations in echelon form, kept under the lowest variable they use. The low bits of
+        // the first word only ever show up in the very first output, so they're the lowest
+        // variables and the rest can be solved without them. Once every other one has an
+        // equation, the observations left over just get checked against the clone.
+        let mut pivots: Vec<Option<Gf2Equation>> = vec![None; variables];
+        let mut unpinned = variables - p.r as usize;
+        'observations: for (index, observed) in by_index.iter().enumerate() {
+            if index > 0 && index % p.n == 0 {
+                state = twist_symbols(&p, state);
+            }
+            let word = &state[index % p.n];
+
+            for &(mask, bits) in observed {
+                for out in (0..p.w).filter(|&out| has_bit(mask, out)) {
+                    let mut row = vec![0; row_len];
+                    for &bit in &tempering[out as usize] {
+                        xor_rows(&mut row, &word[bit]);
+                    }
+                    match eliminate(&mut pivots, row, has_bit(bits, out)) {
+                        Reduction::Pivot(var) if var >= p.r as usize => unpinned -= 1,
+                        Reduction::Contradiction => return None,
+                        _ => {}
+                    }
+                    if unpinned == 0 {
+                        break 'observations;
+                    }
+                }
+            }
+        }
+        if unpinned > 0 {
+            return None;
+        }
+
+        // any low bits of the first word nothing said anything about are left as zeros
+        let solution = back_substitute(&pivots);
+        let mt: Vec<W> = (0..p.n)
+            .map(|word| {
+                (0..p.w)
+                    .filter(|&bit| row_bit(&solution, word * w + bit as usize))
+                    .fold(W::ZERO, |acc, bit| acc | W::ONE.shl(bit))
+            })
+            .collect();
+
+        let mut clone = MersenneTwister::from_state_with(p, &mt, 0);
+        for observed in &by_index {
+            let output = clone.extract_number();
+            if observed.iter().any(|&(mask, bits)| output & mask != bits) {
+                return None;
+            }
+        }
+        Some(clone)
+    }
+}
+
+impl MtStateSolver<u32> {
+    /// Output `index` left `remainder` when divided by `modulus`, as in `rand() % modulus`.
+    /// Only the low bits shared with the powers of two in `modulus` come through.
+    pub fn observe_modulo(&mut self, index: usize, modulus: u32, remainder: u32) {
+        self.observe_low_bits(index, modulus.trailing_zeros() as u8, remainder);
+    }
+
+    /// Output `index` scaled into `0..range` by a widening multiply, so `value` was
+    /// `(output * range) >> 32`. The outputs that give `value` all share their top bits.
+    pub fn observe_range(&mut self, index: usize, range: u32, value: u32) {
+        let range = range as u64;
+        let lowest = ((value as u64) << 32).div_ceil(range);
+        let highest = ((value as u64 + 1) << 32).div_ceil(range) - 1;
+        let known = (lowest ^ highest) as u32;
+        let mask = (!0u32).checked_shl(32 - known.leading_zeros()).unwrap_or(0);
+        self.observe(index, mask, lowest as u32);
+    }
+
+    /// Python's `random.random()` gave `x`, which uses outputs `index` and `index + 1`.
+    pub fn observe_python_random(&mut self, index: usize, x: f64) {
+        let bits = (x * 9007199254740992.0) as u64;
+        self.observe_high_bits(index, 27, (bits >> 26) as u32);
+        self.observe_high_bits(index + 1, 26, bits as u32 & 0x3FFFFFF);
+    }
+}
+
+/// `MersenneTwister::twist` on rows of variables instead of bits.
+fn twist_symbols<W: MtWord>(p: &MtParams<W>, mut state: Vec<Vec<Gf2Row>>) -> Vec<Vec<Gf2Row>> {
+    let (n, w) = (p.n, p.w as usize);
+    for i in 0..n {
+        let x: Vec<&Gf2Row> = (0..w)
+            .map(|bit| match bit < p.r as usize {
+                true => &state[(i + 1) % n][bit],
+                false => &state[i][bit],
+            })
+            .collect();
+
+        let mut word = state[(i + p.m) % n].clone();
+        for (bit, row) in word.iter_mut().enumerate() {
+            if bit + 1 < w {
+                xor_rows(row, x[bit + 1]);
+            }
+            if has_bit(p.a, bit as u8) {
+                xor_rows(row, x[0]);
+            }
+        }
+        state[i] = word;
+    }
+    state
+}
+
+/// Where the time comes from, so code that seeds from the clock or waits around can be run
+/// against a pretend one.
+pub trait Clock {
+    /// Time since the Unix epoch.
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        sleep(duration)
+    }
+}
+
+/// A clock that only moves when something sleeps on it or it's told to.
+pub struct SimulatedClock {
+    now: Cell<Duration>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: Duration) -> Self {
+        Self {
+            now: Cell::new(start),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration)
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+/// Whether a time-based seed counts seconds or milliseconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeedResolution {
+    Seconds,
+    Millis,
+}
+
+impl SeedResolution {
+    /// The seed `time` gives, cut down to 32 bits the way `as u32` does.
+    pub fn seed_at(&self, time: Duration) -> u32 {
+        self.count_at(time) as u32
+    }
+
+    /// Whole units in `time`, for generators that take 64-bit seeds.
+    pub fn count_at(&self, time: Duration) -> u64 {
+        match self {
+            SeedResolution::Seconds => time.as_secs(),
+            SeedResolution::Millis => time.as_millis() as u64,
+        }
+    }
+
+    fn units(&self, count: u64) -> Duration {
+        match self {
+            SeedResolution::Seconds => Duration::from_secs(count),
+            SeedResolution::Millis => Duration::from_millis(count),
+        }
+    }
+}
+
+/// Waits between 40 and 1000 units, seeds an MT19937 with the time, waits again and hands
+/// back the seed and the first number.
+fn mersenne_twister_with_timestamp_seed<C: Clock>(
+    clock: &C,
+    resolution: SeedResolution,
+) -> (u32, u32) {
     let mut rng = thread_rng();
-    let duration = Duration::from_millis(rng.gen_range(40..1000));
-    sleep(duration);
-
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis();
-    let mut mersenne = MersenneTwisterRNG::new(Some(seed as u32));
+    clock.sleep(resolution.units(rng.gen_range(40..1000)));
+
+    let seed = resolution.seed_at(clock.now());
+    let mut mersenne = MersenneTwisterRNG::new(Some(seed));
     let num = mersenne.extract_number();
 
-    let duration = Duration::from_millis(rng.gen_range(40..1000));
-    sleep(duration);
+    clock.sleep(resolution.units(rng.gen_range(40..1000)));
+
+    (seed, num)
+}
+
+/// Seeds from `behind` units before now to `ahead` units after it, closest to now first, so
+/// `behind` and `ahead` cover skew between our clock and the one that made the seed.
+pub fn seeds_around(now: u32, behind: u32, ahead: u32) -> impl Iterator<Item = u32> {
+    offsets_around(behind, ahead).map(move |offset| now.wrapping_add(offset as u32))
+}
 
-    (seed as u32, num)
+/// The steps away from now `seeds_around` goes through, in the same order.
+pub fn offsets_around(behind: u32, ahead: u32) -> impl Iterator<Item = i64> {
+    (0..=behind.max(ahead) as i64).flat_map(move |distance| {
+        let before = (distance <= behind as i64).then_some(-distance);
+        let after = (distance > 0 && distance <= ahead as i64).then_some(distance);
+        before.into_iter().chain(after)
+    })
 }
 
-/// goes back in time to find the seed.
-fn crack_mersenne_seed(generated_number: u32, actual_seed: u32) -> u32 {
-    let mut now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u32;
+/// Goes through the times around now to find the seed of an MT19937 whose first number was
+/// `generated_number`.
+fn crack_mersenne_seed<C: Clock>(
+    clock: &C,
+    resolution: SeedResolution,
+    generated_number: u32,
+    behind: u32,
+    ahead: u32,
+) -> Option<u32> {
+    crack_mersenne_seed_within(clock, resolution, generated_number, behind, ahead, 1)
+        .map(|(seed, _)| seed)
+}
 
-    loop {
-        now -= 1;
-        println!("Now: {} secs\tActual Seed:{}", now, actual_seed);
-        let mut rng = MersenneTwisterRNG::new(Some(now));
-        let num = rng.extract_number();
+/// `crack_mersenne_seed` for when `generated_number` could be any of the first `depth`
+/// numbers, because an unknown number of calls came before it. Gives back the seed and which
+/// number it was, counting from zero.
+fn crack_mersenne_seed_within<C: Clock>(
+    clock: &C,
+    resolution: SeedResolution,
+    generated_number: u32,
+    behind: u32,
+    ahead: u32,
+    depth: usize,
+) -> Option<(u32, usize)> {
+    let now = resolution.seed_at(clock.now());
+    seeds_around(now, behind, ahead).find_map(|seed| {
+        let mut rng = MersenneTwisterRNG::new(Some(seed));
+        (0..depth)
+            .position(|_| rng.extract_number() == generated_number)
+            .map(|k| (seed, k))
+    })
+}
+
+/// `len` bytes of output from an MT19937 seeded with `seed`, four bytes per number.
+fn mt_bytes(seed: u32, len: usize) -> Vec<u8> {
+    let mut rng = MersenneTwisterRNG::new(Some(seed));
+    (0..len.div_ceil(4))
+        .flat_map(|_| rng.extract_number().to_le_bytes())
+        .take(len)
+        .collect()
+}
+
+/// Stream cipher with MT19937 as the keystream. Encrypting and decrypting are the same.
+pub fn mt_stream_cipher(seed: u16, bytes: &[u8]) -> Vec<u8> {
+    fixed_xor(bytes, &mt_bytes(seed as u32, bytes.len()))
+}
+
+struct MtStreamOracle {
+    seed: u16,
+}
 
-        if num == generated_number {
-            // return the seed.
-            return now;
+impl MtStreamOracle {
+    pub fn new() -> Self {
+        Self {
+            seed: thread_rng().gen(),
         }
     }
+
+    /// Encrypts `known` behind a random number of random bytes.
+    pub fn encrypt(&self, known: &[u8]) -> Vec<u8> {
+        let mut rng = thread_rng();
+        let mut plain_text: Vec<u8> = (0..rng.gen_range(5..40)).map(|_| rng.gen()).collect();
+        plain_text.extend_from_slice(known);
+
+        mt_stream_cipher(self.seed, &plain_text)
+    }
+}
+
+struct MtStreamAttacker;
+
+impl MtStreamAttacker {
+    /// There are only 2^16 seeds, so tries them all against the end of the keystream, which is
+    /// what `known_suffix` was encrypted with.
+    pub fn recover_seed(&self, cipher: &[u8], known_suffix: &[u8]) -> Option<u16> {
+        let start = cipher.len().checked_sub(known_suffix.len())?;
+        let keystream = fixed_xor(&cipher[start..], known_suffix);
+
+        (0..=u16::MAX).find(|&seed| mt_bytes(seed as u32, cipher.len())[start..] == keystream[..])
+    }
+}
+
+/// A 16-byte password reset token from an MT19937 seeded with the current time in seconds.
+fn password_reset_token<C: Clock>(clock: &C) -> Vec<u8> {
+    mt_bytes(SeedResolution::Seconds.seed_at(clock.now()), 16)
+}
+
+/// Whether `token` was made by an MT19937 seeded with the time in seconds, at most `window`
+/// seconds before now.
+fn is_time_seeded_token<C: Clock>(clock: &C, token: &[u8], window: u32) -> bool {
+    let now = SeedResolution::Seconds.seed_at(clock.now());
+    seeds_around(now, window, 0).any(|seed| mt_bytes(seed, token.len()) == token)
 }
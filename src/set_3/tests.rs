@@ -1,41 +1,189 @@
 #[cfg(test)]
 pub mod set_3_tests {
     use base64::{engine::general_purpose, Engine};
+    use rand::{thread_rng, Rng, RngCore, SeedableRng};
+    use regex::Regex;
     use std::fs;
+    use std::time::Duration;
 
-    use crate::set_3::{break_fixed_nonce_ctr, crack_mersenne_seed, mersenne_twister_with_timestamp_seed, use_ctr_mode, MersenneTwisterRNG, PaddingOracle, PaddingOracleAttacker};
+    use crate::set_1::fixed_xor;
+    use crate::set_2::{has_padding, strip_padding};
+    use crate::set_3::{
+        break_fixed_nonce_ctr, crack_mersenne_seed, crack_mersenne_seed_within,
+        is_time_seeded_token, mersenne_twister_with_timestamp_seed, mt_stream_cipher,
+        password_reset_token, untemper, untemper_64, use_ctr_mode, Clock, CribDragger,
+        ErrorMessageOracle, MersenneTwister, MersenneTwister64, MersenneTwisterRNG, MtParams,
+        MtStateSolver, MtStreamAttacker, MtStreamOracle, NoisyPaddingOracle, PaddingAttackBudget,
+        PaddingAttackError, PaddingOracle, PaddingOracleAttacker, SeedResolution, SimulatedClock,
+        TimingOracle, MT19937, MT19937_64, PADDING_ORACLE_PLAIN_TEXTS,
+    };
 
     #[test]
     fn test_padding_oracle_attack() {
         let oracle = PaddingOracle::new();
         let attacker = PaddingOracleAttacker;
 
-        let ciphers = [
-            "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
-            "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
-            "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
-            "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
-            "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
-            "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==",
-            "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
-            "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
-            "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
-            "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
-        ];
+        for plain_text in PADDING_ORACLE_PLAIN_TEXTS {
+            let decoded = general_purpose::STANDARD.decode(plain_text).unwrap();
+            let (cipher, iv) = oracle.encrypt(&decoded);
 
-        let mut encrypted_ciphers = vec![vec![]];
-        for cipher in ciphers {
-            let decoded = general_purpose::STANDARD.decode(cipher).unwrap();
-            let encrypted = oracle.encrypt(&decoded);
-            encrypted_ciphers.push(encrypted)
+            let recovered = attacker.attack(|iv, c| oracle.is_padding_valid(iv, c), &iv, &cipher);
+            assert_eq!(Some(decoded), recovered);
         }
 
-        for v in encrypted_ciphers {
-            let line = attacker.attack(&oracle, &v);
-            println!("{}", line)
+        // an oracle that doesn't lie needs no second opinion
+        let decoded = general_purpose::STANDARD
+            .decode(PADDING_ORACLE_PLAIN_TEXTS[0])
+            .unwrap();
+        let (cipher, iv) = oracle.encrypt(&decoded);
+        let valid = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let recovered = attacker.attack_noisy(valid, &iv, &cipher, 0, 1, 1);
+        assert_eq!(Some(decoded), recovered);
+    }
+
+    #[test]
+    fn test_padding_oracle_attack_needs_whole_blocks() {
+        let oracle = PaddingOracle::new();
+        let oracle_fn = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let attacker = PaddingOracleAttacker;
+        let (cipher, iv) = oracle.encrypt(b"YELLOW SUBMARINE");
+
+        assert_eq!(None, attacker.attack(oracle_fn, &iv, &[]));
+        assert_eq!(None, attacker.attack(oracle_fn, &iv, &cipher[..15]));
+        assert_eq!(None, attacker.attack(oracle_fn, &iv[..8], &cipher));
+        assert_eq!(None, attacker.attack_noisy(oracle_fn, &iv, &[], 2, 1, 1));
+
+        let budget = PaddingAttackBudget {
+            max_queries: None,
+            time_limit: None,
+        };
+        let res = attacker.attack_parallel(&oracle_fn, &iv, &cipher[..20], &budget);
+        assert_eq!(Some(PaddingAttackError::NotWholeBlocks), res.err());
+    }
+
+    #[test]
+    fn test_padding_oracle_last_byte_false_positive() {
+        // with an identity block cipher this block already ends in \x02\x02, which gets
+        // accepted before the guess that actually produces \x01
+        let block = *b"fourteen bytes\x02\x02";
+        let oracle = |iv: &[u8], c: &[u8]| has_padding(&fixed_xor(iv, c));
+        let attacker = PaddingOracleAttacker;
+
+        assert_eq!(Some(block), attacker.recover_intermediate(&oracle, &block));
+    }
+
+    #[test]
+    fn test_padding_oracle_forgery() {
+        let oracle = PaddingOracle::new();
+        let attacker = PaddingOracleAttacker;
+        let plain_text = br#"{"user":"mallory","role":"admin","expires":null}"#;
+
+        let (iv, cipher) = attacker
+            .forge_ciphertext(|iv, c| oracle.is_padding_valid(iv, c), plain_text)
+            .unwrap();
+        // 48 bytes of JSON plus a whole block of padding
+        assert_eq!(64, cipher.len());
+
+        let decrypted = oracle.decrypt(&cipher, &iv).unwrap();
+        assert_eq!(plain_text.as_slice(), strip_padding(&decrypted));
+    }
+
+    #[test]
+    fn test_parallel_padding_oracle_attack() {
+        let oracle = PaddingOracle::new();
+        let oracle_fn = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let attacker = PaddingOracleAttacker;
+        let budget = PaddingAttackBudget {
+            max_queries: None,
+            time_limit: None,
+        };
+
+        for plain_text in PADDING_ORACLE_PLAIN_TEXTS {
+            let decoded = general_purpose::STANDARD.decode(plain_text).unwrap();
+            let (cipher, iv) = oracle.encrypt(&decoded);
+
+            let report = attacker
+                .attack_parallel(&oracle_fn, &iv, &cipher, &budget)
+                .unwrap();
+            assert_eq!(decoded, report.plain_text);
+
+            // trying bytes in order averages 128 queries per byte; the prior should do far better
+            let blocks = cipher.len() / 16;
+            assert_eq!(blocks, report.queries_per_block.len());
+            assert!(report.total_queries() < blocks * 16 * 128);
         }
     }
 
+    #[test]
+    fn test_padding_oracle_attack_budget() {
+        let oracle = PaddingOracle::new();
+        let oracle_fn = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let attacker = PaddingOracleAttacker;
+        let (cipher, iv) = oracle.encrypt(&oracle.pick_random_plain_text());
+
+        let budget = PaddingAttackBudget {
+            max_queries: Some(100),
+            time_limit: None,
+        };
+        let res = attacker.attack_parallel(&oracle_fn, &iv, &cipher, &budget);
+        assert_eq!(Some(PaddingAttackError::QueryBudgetExhausted), res.err());
+
+        let budget = PaddingAttackBudget {
+            max_queries: None,
+            time_limit: Some(Duration::ZERO),
+        };
+        let res = attacker.attack_parallel(&oracle_fn, &iv, &cipher, &budget);
+        assert_eq!(Some(PaddingAttackError::TimedOut), res.err());
+    }
+
+    #[test]
+    fn test_error_message_padding_oracle() {
+        let noisy = NoisyPaddingOracle::new(0.05, 1337);
+        let oracle = ErrorMessageOracle {
+            query: |iv: &[u8], c: &[u8]| {
+                let response = noisy.respond(iv, c);
+                format!("{} {}", response.status, response.body)
+            },
+            valid: Regex::new("^403 .*authentication failed").unwrap(),
+        };
+        let attacker = PaddingOracleAttacker;
+
+        let plain_text = general_purpose::STANDARD
+            .decode(PADDING_ORACLE_PLAIN_TEXTS[1])
+            .unwrap();
+        let (cipher, iv) = noisy.encrypt(&plain_text);
+
+        let valid = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let recovered = attacker.attack_noisy(valid, &iv, &cipher, 6, 5, 5);
+        assert_eq!(Some(plain_text), recovered);
+    }
+
+    #[test]
+    fn test_timing_padding_oracle() {
+        let noisy = NoisyPaddingOracle::new(0.05, 42);
+        let oracle = TimingOracle::new(
+            |iv: &[u8], c: &[u8]| noisy.respond(iv, c).elapsed,
+            Duration::from_micros(1450),
+            5,
+        );
+        let attacker = PaddingOracleAttacker;
+
+        let plain_text = general_purpose::STANDARD
+            .decode(PADDING_ORACLE_PLAIN_TEXTS[4])
+            .unwrap();
+        let (cipher, iv) = noisy.encrypt(&plain_text);
+
+        let valid = |iv: &[u8], c: &[u8]| oracle.is_padding_valid(iv, c);
+        let recovered = attacker.attack_noisy(valid, &iv, &cipher, 2, 5, 5);
+        assert_eq!(Some(plain_text), recovered);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_timing_padding_oracle_needs_a_sample() {
+        TimingOracle::new(|_: &[u8], _: &[u8]| Duration::ZERO, Duration::ZERO, 0);
+    }
+
     #[test]
     fn test_ctr_mode() {
         let s = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
@@ -53,14 +201,87 @@ pub mod set_3_tests {
     #[test]
     fn test_break_fixed_nonce_ctr() {
         let text = fs::read_to_string("texts/20.txt").unwrap();
-        let mut decoded_res = vec![];
-        for line in text.lines() {
-            let d = general_purpose::STANDARD.decode(line).unwrap();
-            decoded_res.push(d);
+        let key: [u8; 16] = thread_rng().gen();
+        let nonce = [0; 8];
+
+        let plain_texts: Vec<Vec<u8>> = text
+            .lines()
+            .map(|line| general_purpose::STANDARD.decode(line).unwrap())
+            .collect();
+        let ciphers: Vec<Vec<u8>> = plain_texts
+            .iter()
+            .map(|p| use_ctr_mode(&key, &nonce, p))
+            .collect();
+        let longest = ciphers.iter().map(|c| c.len()).max().unwrap();
+        let actual_keystream = use_ctr_mode(&key, &nonce, &vec![0; longest]);
+
+        let keystream = break_fixed_nonce_ctr(&ciphers, 10);
+        assert_eq!(actual_keystream.len(), keystream.len());
+
+        for (column, guess) in keystream.iter().enumerate() {
+            let samples = ciphers.iter().filter(|c| c.len() > column).count();
+            if samples < 10 {
+                assert_eq!(None, *guess);
+                continue;
+            }
+
+            // every line starts with a capital, and a wrong guess anywhere else has to say it
+            // isn't sure
+            let guess = guess.unwrap();
+            if column == 0 {
+                assert_eq!(actual_keystream[column], guess.value);
+            }
+            assert!(
+                guess.value == actual_keystream[column] || guess.confidence <= 0.9,
+                "column {column} guessed {:?} for {}",
+                guess,
+                actual_keystream[column]
+            );
+        }
+    }
+
+    #[test]
+    fn test_crib_dragging_fixed_nonce_ctr() {
+        let text = fs::read_to_string("texts/20.txt").unwrap();
+        let key: [u8; 16] = thread_rng().gen();
+        let nonce = [0; 8];
+
+        let plain_texts: Vec<Vec<u8>> = text
+            .lines()
+            .map(|line| general_purpose::STANDARD.decode(line).unwrap())
+            .collect();
+        let ciphers: Vec<Vec<u8>> = plain_texts
+            .iter()
+            .map(|p| use_ctr_mode(&key, &nonce, p))
+            .collect();
+
+        let mut dragger = CribDragger::new(ciphers.clone());
+        dragger.seed(&break_fixed_nonce_ctr(&ciphers, 5), 0.9);
+        for (line, plain_text) in dragger.decrypt().iter().zip(&plain_texts) {
+            for (guess, actual) in line.iter().zip(plain_text) {
+                assert!(guess.is_none() || *guess == Some(*actual));
+            }
         }
 
-        let actual = break_fixed_nonce_ctr(decoded_res);
-        assert!(actual.contains("Rakim, check this out, yo "));
+        // only the two longest lines reach this far, so the crib can't tell which one it's in
+        let crib = b" the money";
+        let matches = dragger.drag(crib);
+        for m in &matches[..2] {
+            assert_eq!(106, m.offset);
+            assert!([26, 46].contains(&m.crib_in));
+        }
+        let in_46 = matches.iter().find(|m| m.crib_in == 46).unwrap();
+        assert_eq!(vec![(26, b"hole scene".to_vec())], in_46.revealed);
+
+        dragger.pin(46, 106, crib);
+        dragger.pin(26, 111, b"scenery");
+        let decrypted = dragger.decrypt();
+        for line in [26, 46] {
+            let tail: Vec<u8> = decrypted[line][106..].iter().map(|b| b.unwrap()).collect();
+            assert_eq!(plain_texts[line][106..], tail);
+        }
+
+        assert!(dragger.drag(b"").is_empty());
     }
 
     #[test]
@@ -85,8 +306,387 @@ pub mod set_3_tests {
 
     #[test]
     fn test_mersenne_seed_cracker() {
-      let (expected_seed, generated_number) = mersenne_twister_with_timestamp_seed();
-      let actual_seed = crack_mersenne_seed(generated_number, expected_seed);
-      assert_eq!(expected_seed, actual_seed)
+        for resolution in [SeedResolution::Seconds, SeedResolution::Millis] {
+            let clock = SimulatedClock::new(Duration::from_secs(1_700_000_000));
+            let (expected_seed, generated_number) =
+                mersenne_twister_with_timestamp_seed(&clock, resolution);
+            let actual_seed = crack_mersenne_seed(&clock, resolution, generated_number, 2000, 0);
+            assert_eq!(Some(expected_seed), actual_seed);
+
+            // our clock running behind theirs puts the seed in the future
+            let behind = SimulatedClock::new(clock.now() - resolution.units(3000));
+            assert_eq!(
+                Some(expected_seed),
+                crack_mersenne_seed(&behind, resolution, generated_number, 0, 5000)
+            );
+            assert_eq!(
+                None,
+                crack_mersenne_seed(&behind, resolution, generated_number, 5000, 100)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mersenne_twister_clone() {
+        let mut rng = MersenneTwisterRNG::new(Some(0xC0FFEE));
+        let outputs: Vec<u32> = (0..1000).map(|_| rng.extract_number()).collect();
+
+        let mut state = MersenneTwisterRNG::new(Some(0xC0FFEE));
+        state.twist();
+        let untempered: Vec<u32> = outputs[..624].iter().map(|&o| untemper(o)).collect();
+        assert_eq!(state.mt.to_vec(), untempered);
+
+        // starting part way through a twist doesn't matter
+        let mut clone = MersenneTwisterRNG::clone_from_outputs(&outputs[100..]).unwrap();
+        for _ in 0..2000 {
+            assert_eq!(rng.extract_number(), clone.extract_number());
+        }
+
+        assert!(MersenneTwisterRNG::clone_from_outputs(&outputs[..600]).is_none());
+        let mut tampered = outputs.clone();
+        tampered[800] ^= 1;
+        assert!(MersenneTwisterRNG::clone_from_outputs(&tampered).is_none());
+    }
+
+    #[test]
+    fn test_mersenne_seed_cracker_within() {
+        let clock = SimulatedClock::new(Duration::from_secs(1_700_000_000));
+        let seed = SeedResolution::Seconds.seed_at(clock.now());
+        let mut rng = MersenneTwisterRNG::new(Some(seed));
+        for _ in 0..37 {
+            rng.extract_number();
+        }
+        let observed = rng.extract_number();
+        clock.advance(Duration::from_secs(500));
+
+        assert_eq!(
+            Some((seed, 37)),
+            crack_mersenne_seed_within(&clock, SeedResolution::Seconds, observed, 1000, 0, 100)
+        );
+        assert_eq!(
+            None,
+            crack_mersenne_seed_within(&clock, SeedResolution::Seconds, observed, 1000, 0, 37)
+        );
+    }
+
+    #[test]
+    fn test_mersenne_twister_clone_with_gaps() {
+        let mut rng = MersenneTwisterRNG::new(Some(0xFACADE));
+        for _ in 0..150 {
+            rng.extract_number();
+        }
+        let mut watch = |calls: usize, missed: &dyn Fn(usize) -> bool| -> Vec<Option<u32>> {
+            (0..calls)
+                .map(|i| Some(rng.extract_number()).filter(|_| !missed(i)))
+                .collect()
+        };
+
+        // 624 seen, with 76 calls in the middle going to someone else
+        let outputs = watch(700, &|i| (397..473).contains(&i));
+        let mut clone = MersenneTwisterRNG::clone_from_gapped_outputs(&outputs).unwrap();
+        let outputs = watch(1500, &|i| i % 5 == 2);
+        for output in &outputs {
+            let next = clone.extract_number();
+            assert!(output.map_or(true, |output| output == next));
+        }
+
+        // one call in five missed
+        let mut clone = MersenneTwisterRNG::clone_from_gapped_outputs(&outputs).unwrap();
+        for output in watch(100, &|_| false).into_iter().flatten() {
+            assert_eq!(output, clone.extract_number());
+        }
+
+        // the same gap moved up leaves too little after it to fill in the end of it
+        let outputs = watch(700, &|i| (400..476).contains(&i));
+        assert!(MersenneTwisterRNG::clone_from_gapped_outputs(&outputs).is_none());
+
+        // without the top bit of `a` the twist can't be undone, but running it forwards and
+        // backwards still fills in one call in five
+        let params = MtParams {
+            a: MT19937.a & 0x7FFFFFFF,
+            ..MT19937
+        };
+        let mut rng = MersenneTwister::with_params(params, 0xFACADE);
+        let outputs: Vec<Option<u32>> = (0..1500)
+            .map(|i| Some(rng.extract_number()).filter(|_| i % 5 != 2))
+            .collect();
+        let mut clone = MersenneTwister::clone_from_gapped_outputs_with(params, &outputs).unwrap();
+        for _ in 0..100 {
+            assert_eq!(rng.extract_number(), clone.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_mt_stream_cipher_seed_recovery() {
+        let cipher = mt_stream_cipher(0xBEEF, b"attack at dawn");
+        assert_eq!(
+            b"attack at dawn".to_vec(),
+            mt_stream_cipher(0xBEEF, &cipher)
+        );
+
+        let oracle = MtStreamOracle::new();
+        let cipher = oracle.encrypt(b"AAAAAAAAAAAAAA");
+        let seed = MtStreamAttacker.recover_seed(&cipher, b"AAAAAAAAAAAAAA");
+        assert_eq!(Some(oracle.seed), seed);
+    }
+
+    #[test]
+    fn test_password_reset_token_detection() {
+        let clock = SimulatedClock::new(Duration::from_secs(1_700_000_000));
+        let token = password_reset_token(&clock);
+
+        clock.advance(Duration::from_secs(30));
+        assert!(is_time_seeded_token(&clock, &token, 60));
+        assert!(!is_time_seeded_token(&clock, &token, 10));
+        assert!(!is_time_seeded_token(&clock, &[0x42; 16], 60));
+    }
+
+    #[test]
+    fn test_mersenne_twister_reference_outputs() {
+        // the 10000th output of a default-constructed std::mt19937 and std::mt19937_64
+        let mut rng = MersenneTwisterRNG::new(None);
+        assert_eq!(
+            Some(4123659995),
+            (0..10000).map(|_| rng.extract_number()).last()
+        );
+
+        let mut rng = MersenneTwister64::new(None);
+        assert_eq!(14514284786278117030, rng.extract_number());
+        assert_eq!(
+            Some(9981545732273789042),
+            (1..10000).map(|_| rng.extract_number()).last()
+        );
+    }
+
+    #[test]
+    fn test_mersenne_twister_narrow_parameters() {
+        // MT19937 run on 64-bit words has to stay in the low 32 bits
+        let widened = MtParams {
+            w: 32,
+            n: 624,
+            m: 397,
+            r: 31,
+            a: 0x9908B0DFu64,
+            u: 11,
+            d: 0xFFFFFFFF,
+            s: 7,
+            b: 0x9D2C5680,
+            t: 15,
+            c: 0xEFC60000,
+            l: 18,
+            f: 1812433253,
+        };
+        let mut wide = MersenneTwister::with_params(widened, 1131464071);
+        let mut rng = MersenneTwisterRNG::new(Some(1131464071));
+        let outputs: Vec<u64> = (0..1000).map(|_| wide.extract_number()).collect();
+        for &output in &outputs {
+            assert_eq!(rng.extract_number() as u64, output);
+        }
+
+        let mut clone = MersenneTwister::clone_from_outputs_with(widened, &outputs).unwrap();
+        assert_eq!(rng.extract_number() as u64, clone.extract_number());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mersenne_twister_split_must_be_inside_the_word() {
+        MersenneTwister::with_params(MtParams { r: 40, ..MT19937 }, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mersenne_twister_state_must_be_n_words() {
+        MersenneTwister::from_state_with(MT19937, &[0; 100], 0);
+    }
+
+    #[test]
+    fn test_mersenne_twister_64_clone() {
+        let mut rng = MersenneTwister64::new(Some(0xDEADBEEFCAFE));
+        let outputs: Vec<u64> = (0..500).map(|_| rng.extract_number()).collect();
+
+        let mut state = MersenneTwister64::new(Some(0xDEADBEEFCAFE));
+        state.twist();
+        let untempered: Vec<u64> = outputs[..312].iter().map(|&o| untemper_64(o)).collect();
+        assert_eq!(state.mt, untempered);
+
+        let mut clone = MersenneTwister64::clone_from_outputs(&outputs[50..]).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), clone.extract_number());
+        }
+        assert!(MersenneTwister64::clone_from_outputs(&outputs[..311]).is_none());
+    }
+
+    #[test]
+    fn test_mersenne_twister_init_by_array() {
+        // the reference outputs that ship with mt19937ar.c and mt19937-64.c
+        let mut rng = MersenneTwisterRNG::with_key(MT19937, &[0x123, 0x234, 0x345, 0x456]);
+        let outputs: Vec<u32> = (0..5).map(|_| rng.extract_number()).collect();
+        assert_eq!(
+            vec![1067595299, 955945823, 477289528, 4107218783, 4228976476],
+            outputs
+        );
+
+        let mut rng =
+            MersenneTwister64::with_key(MT19937_64, &[0x12345, 0x23456, 0x34567, 0x45678]);
+        let outputs: Vec<u64> = (0..5).map(|_| rng.extract_number()).collect();
+        assert_eq!(
+            vec![
+                7266447313870364031,
+                4946485549665804864,
+                16945909448695747420,
+                16394063075524226720,
+                4873882236456199058
+            ],
+            outputs
+        );
+    }
+
+    #[test]
+    fn test_language_compatible_outputs() {
+        // random.seed(42); random.random(), random.random()
+        let mut python = MersenneTwisterRNG::from_python_seed(42);
+        assert_eq!(0.6394267984578837, python.python_random());
+        assert_eq!(0.025010755222666936, python.python_random());
+
+        let mut python = MersenneTwisterRNG::from_python_seed(12345678901234567890);
+        assert_eq!(
+            707656484213018010632064310826,
+            python.python_getrandbits(100)
+        );
+        assert_eq!(82, python.python_getrandbits(7));
+
+        // mt_srand(1); mt_rand(), mt_rand()
+        let mut php = MersenneTwisterRNG::from_php_seed(1);
+        assert_eq!(895547922, php.php_mt_rand());
+        assert_eq!(2141438069, php.php_mt_rand());
+
+        let mut php = MersenneTwisterRNG::from_php_seed(42);
+        let rolls: Vec<i64> = (0..5).map(|_| php.php_mt_rand_range(1, 100)).collect();
+        assert_eq!(vec![43, 68, 77, 15, 27], rolls);
+
+        // wider than 32 bits, two numbers make one
+        let mut php = MersenneTwisterRNG::from_php_seed(42);
+        let mut raw = MersenneTwisterRNG::from_php_seed(42);
+        let mut raw_u64 = || (raw.extract_number() as u64) << 32 | raw.extract_number() as u64;
+        assert_eq!(
+            (raw_u64() & ((1 << 40) - 1)) as i64,
+            php.php_mt_rand_range(0, (1 << 40) - 1)
+        );
+        assert_eq!(
+            -(1 << 40) + (raw_u64() % ((1 << 41) + 1)) as i64,
+            php.php_mt_rand_range(-(1 << 40), 1 << 40)
+        );
+        assert_eq!(
+            i64::MIN.wrapping_add(raw_u64() as i64),
+            php.php_mt_rand_range(i64::MIN, i64::MAX)
+        );
+
+        // Random.new(42).rand, then rand(100) five times
+        let mut ruby = MersenneTwisterRNG::from_ruby_seed(42);
+        assert_eq!(0.3745401188473625, ruby.ruby_rand());
+        let mut ruby = MersenneTwisterRNG::from_ruby_seed(42);
+        let rolls: Vec<u64> = (0..5).map(|_| ruby.ruby_rand_int(100)).collect();
+        assert_eq!(vec![51, 92, 14, 71, 60], rolls);
+
+        // a seed over 32 bits goes through init_by_array, same as Python
+        let mut ruby = MersenneTwisterRNG::from_ruby_seed((1 << 40) + 5);
+        assert_eq!(0.5043802970418443, ruby.ruby_rand());
+    }
+
+    #[test]
+    fn test_predicting_python_from_getrandbits() {
+        let mut python = MersenneTwisterRNG::from_python_seed(0x5EED);
+        let observed: Vec<u32> = (0..624)
+            .map(|_| python.python_getrandbits(32) as u32)
+            .collect();
+
+        let mut clone = MersenneTwisterRNG::clone_from_outputs(&observed).unwrap();
+        assert_eq!(python.python_random(), clone.python_random());
+        assert_eq!(
+            python.python_getrandbits(128),
+            clone.python_getrandbits(128)
+        );
+    }
+
+    #[test]
+    fn test_cloning_through_rand_api() {
+        let mut rng = MersenneTwisterRNG::seed_from_u64(0xDEAD_BEEF_CAFE);
+        let mut tokens = vec![[0u8; 16]; 156];
+        for token in tokens.iter_mut() {
+            rng.fill_bytes(token);
+        }
+
+        let mut clone = MersenneTwisterRNG::clone_from_bytes(&tokens.concat()).unwrap();
+        let (mut next, mut predicted) = ([0u8; 16], [0u8; 16]);
+        rng.fill_bytes(&mut next);
+        clone.fill_bytes(&mut predicted);
+        assert_eq!(next, predicted);
+
+        // the oracle's key and IV are no longer secret
+        let oracle = PaddingOracle::with_rng(&mut rng);
+        let predicted_oracle = PaddingOracle::with_rng(&mut clone);
+        assert_eq!(oracle.encrypt(b"hello"), predicted_oracle.encrypt(b"hello"));
+        assert_eq!(rng.next_u64(), clone.next_u64());
+
+        assert!(MersenneTwisterRNG::clone_from_bytes(&tokens.concat()[..2492]).is_none());
+        assert_eq!(
+            MersenneTwisterRNG::from_seed(42u32.to_le_bytes()).next_u32(),
+            MersenneTwisterRNG::new(Some(42)).extract_number()
+        );
+    }
+
+    #[test]
+    fn test_mt_state_from_partial_outputs() {
+        // rand() % 1024 and rolls of 1000 in turn, with every fifth number not seen at all,
+        // starting part way through a twist
+        let mut rng = MersenneTwisterRNG::new(Some(0x5EED));
+        for _ in 0..100 {
+            rng.extract_number();
+        }
+        let mut solver = MtStateSolver::new(MT19937);
+        for index in 0..3000 {
+            let output = rng.extract_number();
+            match index % 5 {
+                4 => {}
+                i if i % 2 == 0 => solver.observe_modulo(index, 1024, output % 1024),
+                _ => solver.observe_range(index, 1000, ((output as u64 * 1000) >> 32) as u32),
+            }
+        }
+
+        let mut clone = solver.solve().unwrap();
+        // the last number wasn't seen, so the clone is one behind
+        clone.extract_number();
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), clone.extract_number());
+        }
+
+        let mut too_few = MtStateSolver::new(MT19937);
+        for index in 0..600 {
+            too_few.observe_high_bits(index, 31, rng.extract_number() >> 1);
+        }
+        assert!(too_few.solve().is_none());
+    }
+
+    #[test]
+    fn test_mt_state_from_python_floats() {
+        let mut python = MersenneTwisterRNG::from_python_seed(31337);
+        let mut solver = MtStateSolver::new(MT19937);
+        for i in 0..700 {
+            solver.observe_python_random(2 * i, python.python_random());
+        }
+
+        let mut clone = solver.solve().unwrap();
+        assert_eq!(python.python_random(), clone.python_random());
+
+        // one bit seen two different ways
+        solver.observe_high_bits(0, 1, 0);
+        solver.observe_high_bits(0, 1, 1);
+        assert!(solver.solve().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mt_state_solver_high_bits_fit_the_word() {
+        MtStateSolver::new(MT19937).observe_high_bits(0, 33, 0);
     }
 }
@@ -0,0 +1,293 @@
+#![allow(dead_code)]
+
+mod tests;
+
+use rand::{thread_rng, Rng};
+
+use crate::set_1::fixed_xor;
+use crate::set_2::{cbc_decryption, cbc_encryption, is_sanitized, userdata_cookie};
+use crate::set_3::{ctr_keystream, use_ctr_mode};
+
+struct CtrEncryptionOracle {
+    key: [u8; 16],
+    nonce: [u8; 8],
+}
+
+impl CtrEncryptionOracle {
+    pub fn new() -> Self {
+        let mut rng = thread_rng();
+        CtrEncryptionOracle {
+            key: rng.gen(),
+            nonce: rng.gen(),
+        }
+    }
+
+    pub fn encrypt(&self, payload: &str) -> Vec<u8> {
+        let s = userdata_cookie(payload);
+        use_ctr_mode(&self.key, &self.nonce, s.as_bytes())
+    }
+}
+
+struct CtrAttacker;
+
+impl CtrAttacker {
+    pub fn make_admin(&self, oracle: &CtrEncryptionOracle) -> Vec<u8> {
+        let desired = b";admin=true;";
+        // length of "comment1=cooking%20MCs;userdata="
+        let prefix_len = 32;
+
+        // send a stand-in for every byte that gets quoted out. flipping a bit of the cipher
+        // flips the same bit of the plain text, so no block gets garbled on the way.
+        let payload: String = desired
+            .iter()
+            .map(|&b| if is_sanitized(b) { 'A' } else { b as char })
+            .collect();
+
+        let mut cipher = oracle.encrypt(&payload);
+        for (i, (&wanted, sent)) in desired.iter().zip(payload.bytes()).enumerate() {
+            cipher[prefix_len + i] ^= wanted ^ sent;
+        }
+
+        cipher
+    }
+
+    pub fn check_is_admin(&self, oracle: &CtrEncryptionOracle, cipher: &[u8]) -> bool {
+        let decrypted = use_ctr_mode(&oracle.key, &oracle.nonce, cipher);
+        let p: String = decrypted.into_iter().map(|c| c as char).collect();
+
+        p.contains(";admin=true;")
+    }
+}
+
+/// Seeks into the CTR cipher text and re-encrypts it with `new_text` starting at `offset`.
+/// The cipher text grows if `new_text` runs past its end. An `offset` past the end leaves a gap
+/// that gets encrypted zero bytes, so it decrypts to zeros rather than the bare keystream.
+pub fn edit(cipher: &[u8], key: &[u8], nonce: &[u8], offset: usize, new_text: &[u8]) -> Vec<u8> {
+    let start = offset.min(cipher.len());
+    let mut text = vec![0; offset - start];
+    text.extend_from_slice(new_text);
+
+    let keystream = ctr_keystream(key, nonce, start, text.len());
+    let encrypted = fixed_xor(&keystream, &text);
+
+    let mut res = cipher.to_vec();
+    res.resize(res.len().max(start + encrypted.len()), 0);
+    res[start..start + encrypted.len()].copy_from_slice(&encrypted);
+
+    res
+}
+
+/// Exposes `edit` to the attacker without handing out the key.
+struct CtrEditOracle {
+    key: [u8; 16],
+    nonce: [u8; 8],
+}
+
+impl CtrEditOracle {
+    pub fn new() -> Self {
+        let mut rng = thread_rng();
+        CtrEditOracle {
+            key: rng.gen(),
+            nonce: rng.gen(),
+        }
+    }
+
+    pub fn encrypt(&self, plain_text: &[u8]) -> Vec<u8> {
+        use_ctr_mode(&self.key, &self.nonce, plain_text)
+    }
+
+    /// `edit` under the oracle's key and nonce, gaps past the end included.
+    pub fn edit(&self, cipher: &[u8], offset: usize, new_text: &[u8]) -> Vec<u8> {
+        edit(cipher, &self.key, &self.nonce, offset, new_text)
+    }
+}
+
+struct CtrEditAttacker;
+
+impl CtrEditAttacker {
+    /// Rewriting the whole cipher text with zeroes hands back the raw keystream.
+    pub fn recover_plain_text(&self, oracle: &CtrEditOracle, cipher: &[u8]) -> Vec<u8> {
+        let keystream = oracle.edit(cipher, 0, &vec![0; cipher.len()]);
+        fixed_xor(&keystream, cipher)
+    }
+}
+
+/// The bit-flipping oracle again, except the key doubles as the IV.
+struct CbcKeyAsIvOracle {
+    key: [u8; 16],
+}
+
+impl CbcKeyAsIvOracle {
+    pub fn new() -> Self {
+        let mut rng = thread_rng();
+        CbcKeyAsIvOracle { key: rng.gen() }
+    }
+
+    pub fn encrypt(&self, payload: &str) -> Vec<u8> {
+        let s = userdata_cookie(payload);
+        cbc_encryption(&self.key, s.as_bytes(), &self.key)
+    }
+
+    /// Complains with the decrypted bytes when the plain text isn't ASCII.
+    pub fn decrypt(&self, cipher: &[u8]) -> Result<(), Vec<u8>> {
+        let decrypted = cbc_decryption(&self.key, cipher, &self.key);
+
+        if decrypted.is_ascii() {
+            Ok(())
+        } else {
+            Err(decrypted)
+        }
+    }
+}
+
+struct CbcKeyAsIvAttacker;
+
+impl CbcKeyAsIvAttacker {
+    /// Sends `C1 || 0 || C1`. The first block decrypts to `D(C1) ^ key` and the third to `D(C1)`,
+    /// so XORing them gives the key. Returns `None` if the oracle happens not to complain.
+    pub fn recover_key(&self, oracle: &CbcKeyAsIvOracle, cipher: &[u8]) -> Option<Vec<u8>> {
+        let first_block = &cipher[..16];
+        let modified = [first_block, &[0; 16], first_block].concat();
+
+        match oracle.decrypt(&modified) {
+            Ok(()) => None,
+            Err(plain_text) => Some(fixed_xor(&plain_text[..16], &plain_text[32..48])),
+        }
+    }
+}
+
+/// SHA-1, with the registers and the length counter out in the open so a hash can pick up from
+/// any digest it's handed.
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    registers: [u32; 5],
+    /// Bytes hashed so far, including any still waiting in `buffer`.
+    length: u64,
+    buffer: Vec<u8>,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Self::from_state(
+            [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0],
+            0,
+        )
+    }
+
+    /// Starts from `registers` as if `length` bytes had already gone through. `length` has to
+    /// be a whole number of blocks, padding included.
+    pub fn from_state(registers: [u32; 5], length: u64) -> Self {
+        assert!(
+            length % 64 == 0,
+            "length {} isn't a whole number of blocks",
+            length
+        );
+        Sha1 {
+            registers,
+            length,
+            buffer: Vec::with_capacity(64),
+        }
+    }
+
+    /// Splits a digest back into the registers that made it.
+    pub fn registers_from_digest(digest: &[u8; 20]) -> [u32; 5] {
+        std::array::from_fn(|i| u32::from_be_bytes(digest[4 * i..4 * i + 4].try_into().unwrap()))
+    }
+
+    pub fn registers(&self) -> [u32; 5] {
+        self.registers
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+        self.buffer.extend_from_slice(data);
+
+        let whole = self.buffer.len() - self.buffer.len() % 64;
+        let blocks: Vec<u8> = self.buffer.drain(..whole).collect();
+        for block in blocks.chunks(64) {
+            self.compress(block);
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; 20] {
+        let padding = sha1_padding(self.length);
+        let length = self.length;
+        self.update(&padding);
+        self.length = length;
+
+        let mut digest = [0; 20];
+        for (chunk, register) in digest.chunks_mut(4).zip(self.registers) {
+            chunk.copy_from_slice(&register.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.registers;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => (b & c | !b & d, 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => (b & c | b & d | c & d, 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (register, value) in self.registers.iter_mut().zip([a, b, c, d, e]) {
+            *register = register.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The padding SHA-1 puts after a message of `length` bytes: a one bit, zeros up to 56 bytes
+/// into a block, then the length in bits as a big-endian u64.
+pub fn sha1_padding(length: u64) -> Vec<u8> {
+    let zeros = (119 - length % 64) % 64;
+    let mut padding = vec![0x80];
+    padding.resize(1 + zeros as usize, 0);
+    padding.extend_from_slice(&length.wrapping_mul(8).to_be_bytes());
+    padding
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut hash = Sha1::new();
+    hash.update(data);
+    hash.finalize()
+}
+
+/// The naive secret-prefix MAC, `SHA1(key || message)`.
+pub fn sha1_keyed_mac(key: &[u8], message: &[u8]) -> [u8; 20] {
+    let mut hash = Sha1::new();
+    hash.update(key);
+    hash.update(message);
+    hash.finalize()
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod set_4_tests {
+    use base64::{engine::general_purpose, Engine};
+    use std::fs;
+
+    use crate::set_1::decrypt_aes_ecb;
+    use crate::set_2::cbc_decryption;
+    use crate::set_3::use_ctr_mode;
+    use crate::set_4::{
+        edit, sha1, sha1_keyed_mac, sha1_padding, CbcKeyAsIvAttacker, CbcKeyAsIvOracle,
+        CtrAttacker, CtrEditAttacker, CtrEditOracle, CtrEncryptionOracle, Sha1,
+    };
+
+    #[test]
+    fn test_ctr_bit_flipping() {
+        let oracle = CtrEncryptionOracle::new();
+        let attacker = CtrAttacker;
+
+        let honest = oracle.encrypt(";admin=true;");
+        assert!(!attacker.check_is_admin(&oracle, &honest));
+
+        let encrypted = attacker.make_admin(&oracle);
+        assert!(attacker.check_is_admin(&oracle, &encrypted))
+    }
+
+    #[test]
+    fn test_ctr_edit() {
+        let key = "YELLOW SUBMARINE".as_bytes();
+        let nonce = [0u8; 8];
+        let cipher = use_ctr_mode(key, &nonce, b"the quick brown fox jumps over the lazy dog");
+
+        let edited = edit(&cipher, key, &nonce, 20, b"leaps");
+        let decrypted = use_ctr_mode(key, &nonce, &edited);
+        assert_eq!(
+            b"the quick brown fox leaps over the lazy dog".as_slice(),
+            decrypted
+        );
+
+        let extended = edit(&cipher, key, &nonce, 40, b"dog and the cat");
+        let decrypted = use_ctr_mode(key, &nonce, &extended);
+        assert_eq!(
+            b"the quick brown fox jumps over the lazy dog and the cat".as_slice(),
+            decrypted
+        );
+
+        // a gap between the end and the edit decrypts to zeros
+        let gapped = edit(&cipher, key, &nonce, 48, b"!");
+        let decrypted = use_ctr_mode(key, &nonce, &gapped);
+        assert_eq!(49, decrypted.len());
+        assert_eq!([0; 5], decrypted[43..48]);
+        assert_eq!(b'!', decrypted[48]);
+    }
+
+    #[test]
+    fn test_break_random_access_ctr() {
+        let f = fs::read_to_string("texts/7.txt")
+            .map(|s| s.replace('\n', ""))
+            .unwrap();
+        let bytes = general_purpose::STANDARD_NO_PAD.decode(f).unwrap();
+        let plain_text = decrypt_aes_ecb("YELLOW SUBMARINE".as_bytes(), &bytes);
+
+        let oracle = CtrEditOracle::new();
+        let cipher = oracle.encrypt(&plain_text);
+
+        let attacker = CtrEditAttacker;
+
+        assert_eq!(plain_text, attacker.recover_plain_text(&oracle, &cipher));
+    }
+
+    #[test]
+    fn test_recover_key_used_as_iv() {
+        let oracle = CbcKeyAsIvOracle::new();
+        let attacker = CbcKeyAsIvAttacker;
+        let cipher = oracle.encrypt("nothing to see here");
+        assert_eq!(Ok(()), oracle.decrypt(&cipher));
+
+        let key = attacker.recover_key(&oracle, &cipher).unwrap();
+        assert_eq!(oracle.key.as_slice(), key);
+
+        let decrypted = cbc_decryption(&key, &cipher, &key);
+        let s: String = decrypted.into_iter().map(|c| c as char).collect();
+        assert!(s.starts_with("comment1=cooking%20MCs;userdata=nothing to see here;"));
+    }
+
+    #[test]
+    fn test_sha1() {
+        let vectors = [
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            ("abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12",
+            ),
+        ];
+        for (message, digest) in vectors {
+            assert_eq!(hex::encode(sha1(message.as_bytes())), digest);
+        }
+
+        let mut hash = Sha1::new();
+        for _ in 0..1000 {
+            hash.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            hex::encode(hash.finalize()),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn test_sha1_from_state() {
+        let message =
+            b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+        let digest = sha1(message);
+
+        let glued = [message.as_slice(), &sha1_padding(message.len() as u64)].concat();
+        assert_eq!(glued.len() % 64, 0);
+
+        let registers = Sha1::registers_from_digest(&digest);
+        let mut hash = Sha1::from_state(registers, glued.len() as u64);
+        assert_eq!(hash.registers(), registers);
+        hash.update(b";admin=true");
+        assert_eq!(hash.length(), glued.len() as u64 + 11);
+        assert_eq!(
+            hash.finalize(),
+            sha1(&[glued.as_slice(), b";admin=true"].concat())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sha1_from_state_needs_whole_blocks() {
+        Sha1::from_state([0; 5], 65);
+    }
+
+    #[test]
+    fn test_sha1_keyed_mac() {
+        let key = b"YELLOW SUBMARINE";
+        let message = b"we all live in a yellow submarine";
+        let mac = sha1_keyed_mac(key, message);
+
+        assert_eq!(mac, sha1(&[key.as_slice(), message].concat()));
+        assert_ne!(mac, sha1_keyed_mac(b"YELLOW SUBMARINF", message));
+        assert_ne!(
+            mac,
+            sha1_keyed_mac(key, b"we all live in a yellow submarinE")
+        );
+    }
+}
@@ -23,20 +23,6 @@ fn create_corpus(text: String) -> HashMap<char, f64> {
     corpus_map
 }
 
-pub fn get_uppercase_corpus() -> HashMap<char, f64> {
-    let corpus = get_english_corpus();
-    let mut upper_corpus = HashMap::new();
-    for (k, v) in corpus {
-        if !k.is_uppercase() {
-            continue
-        }
-        *upper_corpus.entry(k).or_insert(0f64) += v;
-    }
-
-    upper_corpus
-}
-
-
 pub fn get_score_of_english_chars(text: &str, corpus: &HashMap<char, f64>) -> f64 {
     let mut score = 0.0f64;
     for c in text.chars() {
@@ -48,6 +34,131 @@ pub fn get_score_of_english_chars(text: &str, corpus: &HashMap<char, f64>) -> f6
     score / text.chars().count() as f64
 }
 
+/// Gives both cases of a letter their combined frequency, so a guess can't win just by
+/// picking the more common case.
+pub fn get_case_insensitive_corpus(corpus: &HashMap<char, f64>) -> HashMap<char, f64> {
+    let mut folded = HashMap::new();
+    for (k, v) in corpus {
+        *folded.entry(k.to_ascii_lowercase()).or_insert(0f64) += v;
+    }
+
+    let mut res = folded.clone();
+    for (k, v) in folded {
+        if k.is_ascii_lowercase() {
+            res.insert(k.to_ascii_uppercase(), v);
+        }
+    }
+    res
+}
+
+/// Frequency given to characters that never show up in the corpus, so they cost a lot
+/// instead of ruling a guess out.
+const UNSEEN_CHAR_FREQUENCY: f64 = 1e-6;
+
+/// Log-probability of `bytes` as English, treating every character as independent.
+/// Unlike `get_score_of_english_chars` this adds up across characters instead of averaging,
+/// so it works as a likelihood when comparing guesses.
+pub fn get_log_likelihood_of_english(bytes: &[u8], corpus: &HashMap<char, f64>) -> f64 {
+    bytes
+        .iter()
+        .map(|&b| {
+            corpus
+                .get(&(b as char))
+                .copied()
+                .unwrap_or(UNSEEN_CHAR_FREQUENCY)
+                .ln()
+        })
+        .sum()
+}
+
+/// The per-byte terms of `get_log_likelihood_of_english` as a table, for when the same corpus
+/// scores a lot of guesses and the map lookups start to add up.
+pub fn get_byte_log_likelihoods(corpus: &HashMap<char, f64>) -> [f64; 256] {
+    std::array::from_fn(|b| {
+        corpus
+            .get(&(b as u8 as char))
+            .copied()
+            .unwrap_or(UNSEEN_CHAR_FREQUENCY)
+            .ln()
+    })
+}
+
+/// Chance that two bytes picked from `bytes` are the same. English text sits well above the
+/// 1/256 of random bytes (or 1/26 of random letters), however it's been substituted.
+pub fn index_of_coincidence(bytes: &[u8]) -> f64 {
+    if bytes.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for b in bytes {
+        counts[*b as usize] += 1;
+    }
+
+    let n = bytes.len() as f64;
+    let matches: f64 = counts.iter().map(|&c| (c * c.saturating_sub(1)) as f64).sum();
+    matches / (n * (n - 1.0))
+}
+
+/// Log-probabilities of every four-letter sequence in the corpus, ignoring case and everything
+/// that isn't a letter. Index with `quadgram_index`.
+pub fn get_english_quadgrams() -> Vec<f64> {
+    let texts = fs::read_to_string("texts/pride-and-prejudice.txt").unwrap();
+    let letters: Vec<u8> = texts
+        .bytes()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(|b| b.to_ascii_uppercase() - b'A')
+        .collect();
+
+    let mut counts = vec![0f64; 26 * 26 * 26 * 26];
+    for quadgram in letters.windows(4) {
+        counts[quadgram_index(quadgram)] += 1.0;
+    }
+
+    // quadgrams that never showed up still get a sliver of probability
+    let total = letters.len().saturating_sub(3).max(1) as f64;
+    counts
+        .into_iter()
+        .map(|c| (c.max(0.01) / total).ln())
+        .collect()
+}
+
+/// Position of four letters (each 0..26) in the table from `get_english_quadgrams`.
+pub fn quadgram_index(letters: &[u8]) -> usize {
+    letters[..4]
+        .iter()
+        .fold(0, |acc, &l| acc * 26 + l as usize)
+}
+
+/// How often each byte value shows up in the corpus. Unlike `get_english_corpus` this counts
+/// the raw UTF-8, so the bytes of curly quotes get their own entries.
+pub fn get_english_byte_frequencies() -> Vec<f64> {
+    let texts = fs::read("texts/pride-and-prejudice.txt").unwrap();
+
+    let mut counts = vec![0f64; 256];
+    for b in &texts {
+        counts[*b as usize] += 1.0;
+    }
+    counts.iter().map(|c| c / texts.len().max(1) as f64).collect()
+}
+
+/// Log-probabilities of every pair of consecutive bytes in the corpus, indexed by
+/// `first * 256 + second`. Works on raw bytes, so case, punctuation and whitespace all count.
+pub fn get_english_byte_bigrams() -> Vec<f64> {
+    let texts = fs::read("texts/pride-and-prejudice.txt").unwrap();
+
+    let mut counts = vec![0f64; 256 * 256];
+    for pair in texts.windows(2) {
+        counts[pair[0] as usize * 256 + pair[1] as usize] += 1.0;
+    }
+
+    let total = texts.len().saturating_sub(1).max(1) as f64;
+    counts
+        .into_iter()
+        .map(|c| (c.max(0.01) / total).ln())
+        .collect()
+}
+
 // For binary strings a and b the Hamming distance is equal to the number of ones (population count) in a XOR b
 pub fn hamming_distance(buffer1: &[u8], buffer2: &[u8]) -> u32 {
     buffer1.iter().zip(buffer2.iter())
@@ -69,9 +180,106 @@ pub fn transpose(bytes: &[u8], chunk_size: usize) -> Vec<Vec<u8>> {
 }
 
 
+/// Sets of variables over GF(2), one bit each, to be XORed together.
+pub type Gf2Row = Vec<u64>;
+
+pub fn xor_rows(into: &mut [u64], from: &[u64]) {
+    for (a, b) in into.iter_mut().zip(from) {
+        *a ^= b;
+    }
+}
+
+pub fn row_bit(row: &Gf2Row, bit: usize) -> bool {
+    row[bit / 64] >> (bit % 64) & 1 == 1
+}
+
+pub fn flip_row_bit(row: &mut Gf2Row, bit: usize) {
+    row[bit / 64] ^= 1 << (bit % 64);
+}
+
+/// The variables in `row` XOR to `value`. Words of `row` past `end` are all zeros, which
+/// saves going over them for equations that only use the first few variables.
+#[derive(Clone)]
+pub struct Gf2Equation {
+    row: Gf2Row,
+    value: bool,
+    end: usize,
+}
+
+/// The lowest variable at or after `from` that `row` uses.
+fn lowest_variable(row: &Gf2Row, from: usize) -> Option<usize> {
+    let mut at = from / 64;
+    let mut word = *row.get(at)? & (!0 << (from % 64));
+    while word == 0 {
+        at += 1;
+        word = *row.get(at)?;
+    }
+    Some(at * 64 + word.trailing_zeros() as usize)
+}
+
+/// What became of an equation once the ones before it were taken out.
+pub enum Reduction {
+    /// Something was left, and it's now the equation for this variable.
+    Pivot(usize),
+    /// It followed from the others.
+    Redundant,
+    /// It came down to `0 = 1`.
+    Contradiction,
+}
+
+/// Reduces `row = value` by the equations already in `pivots` and keeps what's left under the
+/// lowest variable it still uses.
+pub fn eliminate(
+    pivots: &mut [Option<Gf2Equation>],
+    mut row: Gf2Row,
+    mut value: bool,
+) -> Reduction {
+    let mut from = 0;
+    while let Some(var) = lowest_variable(&row, from) {
+        match &pivots[var] {
+            Some(pivot) => {
+                let used = var / 64..pivot.end;
+                xor_rows(&mut row[used.clone()], &pivot.row[used]);
+                value ^= pivot.value;
+            }
+            None => {
+                let end = row.iter().rposition(|&word| word != 0).unwrap() + 1;
+                pivots[var] = Some(Gf2Equation { row, value, end });
+                return Reduction::Pivot(var);
+            }
+        }
+        from = var + 1;
+    }
+    match value {
+        true => Reduction::Contradiction,
+        false => Reduction::Redundant,
+    }
+}
+
+/// Reads the values off equations `eliminate` has left in echelon form, from the highest
+/// variable down. Variables nothing pinned down are set to zero.
+pub fn back_substitute(pivots: &[Option<Gf2Equation>]) -> Gf2Row {
+    let mut solution: Gf2Row = vec![0; pivots.len().div_ceil(64)];
+    for var in (0..pivots.len()).rev() {
+        if let Some(equation) = &pivots[var] {
+            let used = var / 64..equation.end;
+            let words = equation.row[used.clone()].iter().zip(&solution[used]);
+            let ones: u32 = words.map(|(a, b)| (a & b).count_ones()).sum();
+            if (ones % 2 == 1) != equation.value {
+                flip_row_bit(&mut solution, var);
+            }
+        }
+    }
+    solution
+}
+
 #[cfg(test)]
 mod utils_test {
-    use crate::utils::{hamming_distance, transpose};
+    use std::collections::HashMap;
+
+    use crate::utils::{
+        get_case_insensitive_corpus, get_log_likelihood_of_english, hamming_distance, transpose,
+    };
 
 
     #[test]
@@ -89,4 +297,17 @@ mod utils_test {
         let res = transpose(&bytes, 4);
         assert_eq!(expected, res);
     }
+
+    #[test]
+    fn test_case_insensitive_log_likelihood() {
+        let corpus = HashMap::from([('a', 0.3), ('A', 0.1), (' ', 0.6)]);
+        let folded = get_case_insensitive_corpus(&corpus);
+
+        let lower = get_log_likelihood_of_english(b"a a", &folded);
+        let upper = get_log_likelihood_of_english(b"A A", &folded);
+        assert!((lower - upper).abs() < 1e-9);
+        assert!((lower - (0.4f64.ln() * 2.0 + 0.6f64.ln())).abs() < 1e-9);
+
+        assert!(get_log_likelihood_of_english(b"a\x00a", &folded) < lower);
+    }
 }
\ No newline at end of file